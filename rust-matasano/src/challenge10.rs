@@ -8,7 +8,8 @@ pub fn run() {
     let iv = &[0;16];
     let decryptor = Aes128CbcDecryptor(iv);
 
-    let result = decryptor.decrypt(&bytes, key);
+    let result = decryptor.decrypt(&bytes, key)
+        .expect("Decryption failed");
     println!("Challenge 10 : {}", String::from_utf8(result).unwrap());
 }
 
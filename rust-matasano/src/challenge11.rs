@@ -38,7 +38,8 @@ pub fn run() {
 
         let input_bytes = to_bytes(&input, &mut random);
         let mut oracle = Oracle::new(None, None);
-        let cipher_bytes = oracle.encrypt_random(&input_bytes);
+        let cipher_bytes = oracle.encrypt_random(&input_bytes)
+            .expect("Encryption failed");
 
         match analyzer::is_ecb(&cipher_bytes, &16) {
             true => cnt_ecb = cnt_ecb + 1,
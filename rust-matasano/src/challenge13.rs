@@ -1,4 +1,5 @@
 use libs::oracle::Oracle;
+use libs::cryptor::CryptoError;
 
 use std::str::from_utf8;
 use regex::Regex;
@@ -9,18 +10,23 @@ pub fn run() {
     // Choose an email id such that the last word of the 
     // profile qurey string ('user') forms a brand new block
     // in the end
-    let mut cipher_bytes = problem.encrypt_profile("zepr@kite.com");
+    let mut cipher_bytes = problem.encrypt_profile("zepr@kite.com")
+        .expect("Encryption failed");
 
     // Remove user
     cipher_bytes.truncate(32);
 
     // Add admin
-    let cipher_admin = get_cipher_admin(&problem);
+    let cipher_admin = get_cipher_admin(&problem)
+        .expect("Encryption failed");
     cipher_bytes.extend(cipher_admin.iter());
-    println!("Challenge 13 : {}", problem.decrypt_profile(&cipher_bytes));
+
+    let profile = problem.decrypt_profile(&cipher_bytes)
+        .expect("Decryption failed");
+    println!("Challenge 13 : {}", profile);
 }
 
-fn get_cipher_admin(problem: &Problem) -> Vec<u8> {
+fn get_cipher_admin(problem: &Problem) -> Result<Vec<u8>, CryptoError> {
     let mut  admin = String::from("admin");
     let pad_byte = 16 - admin.len() as u8;
 
@@ -29,13 +35,13 @@ fn get_cipher_admin(problem: &Problem) -> Vec<u8> {
     }
 
     let input = String::from("foobar@foo") + &admin;
-    let cipher_bytes = problem.encrypt_profile(&input);
+    let cipher_bytes = problem.encrypt_profile(&input)?;
 
-    cipher_bytes.iter()
+    Ok(cipher_bytes.iter()
         .skip(16)
         .take(16)
         .cloned()
-        .collect()
+        .collect())
 }
 
 struct Problem {
@@ -75,15 +81,17 @@ impl Problem {
         String::from("email=") + clean_email + "&uid=10&role=user"
     }
 
-    fn encrypt_profile(&self, email: &str) -> Vec<u8> {
+    fn encrypt_profile(&self, email: &str) -> Result<Vec<u8>, CryptoError> {
         let profile = Problem::profile_for(email);
         self.oracle.encrypt(profile.as_bytes())
     }
 
-    pub fn decrypt_profile(&self, cipher_bytes: &[u8]) -> String {
-        let plain_bytes = self.oracle.decrypt(cipher_bytes);
+    pub fn decrypt_profile(&self, cipher_bytes: &[u8])
+        -> Result<String, CryptoError> {
+
+        let plain_bytes = self.oracle.decrypt(cipher_bytes)?;
         let plain_text = from_utf8(&plain_bytes).unwrap();
-        Problem::decode(plain_text).unwrap()
+        Ok(Problem::decode(plain_text).unwrap())
     }
 }
 
@@ -132,8 +140,8 @@ mod tests {
     #[test]
     fn test_encrypt_decrypt() {
         let problem = Problem::new();
-        let cipher_bytes = problem.encrypt_profile("foo@bar.baz");
-        let profile = problem.decrypt_profile(&cipher_bytes);
+        let cipher_bytes = problem.encrypt_profile("foo@bar.baz").unwrap();
+        let profile = problem.decrypt_profile(&cipher_bytes).unwrap();
         assert_eq!("{\n\temail : 'foo@bar.baz'\n\tuid : '10'\n\trole : 'user'\n}", profile);
     }
 }
@@ -0,0 +1,23 @@
+use std::str::from_utf8;
+
+use libs::utils;
+use libs::oracle::PaddingOracle;
+use libs::analyzer::PaddingOracleAnalyzer;
+
+pub fn run() {
+    let secrets = utils::from_base64_lines("../resources/17.txt");
+    let mut padding_oracle = PaddingOracle::new(secrets);
+
+    let (iv, cipher_bytes) = padding_oracle.encrypt()
+        .expect("Encryption failed");
+
+    let analyzer = PaddingOracleAnalyzer::new(|iv, cipher_bytes| {
+        padding_oracle.is_padding_valid(iv, cipher_bytes)
+    });
+
+    let plain_bytes = analyzer.analyze(&iv, &cipher_bytes)
+        .expect("Error while analyzing padding oracle");
+
+    println!("Challenge 17 : {} ({} queries)", from_utf8(&plain_bytes).unwrap(),
+        analyzer.queries());
+}
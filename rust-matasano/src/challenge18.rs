@@ -0,0 +1,17 @@
+use rustc_serialize::base64::FromBase64;
+
+use libs::cryptor::Decryptor;
+use libs::cryptor::Aes128CtrCryptor;
+use libs::cryptor::CounterFormat;
+
+pub fn run() {
+    let input = "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==";
+    let cipher_bytes = input.from_base64().unwrap();
+    let key = &String::from("YELLOW SUBMARINE").into_bytes();
+    let nonce = &[0; 8];
+    let decryptor = Aes128CtrCryptor::new(nonce, CounterFormat::LittleEndian64);
+
+    let result = decryptor.decrypt(&cipher_bytes, key)
+        .expect("Decryption failed");
+    println!("Challenge 18 : {}", String::from_utf8(result).unwrap());
+}
@@ -0,0 +1,35 @@
+use libs::utils;
+use libs::random::Random;
+use libs::cryptor::Encryptor;
+use libs::cryptor::Aes128CtrCryptor;
+use libs::cryptor::CounterFormat;
+use libs::analyzer::FixedNonceCtrAnalyzer;
+
+pub fn run() {
+    let plain_texts = utils::from_base64_lines("../resources/19.txt");
+    let key = &mut [0; 16];
+    Random::new().fill_bytes(key);
+
+    let nonce = &[0; 8];
+    let cryptor = Aes128CtrCryptor::new(nonce, CounterFormat::LittleEndian64);
+
+    let cipher_texts = plain_texts.iter()
+        .map(|plain_bytes| cryptor.encrypt(plain_bytes, key)
+            .expect("Encryption failed"))
+        .collect::<Vec<Vec<u8>>>();
+
+    let mut analyzer = FixedNonceCtrAnalyzer::new(cipher_texts);
+
+    // Statistics can't tell upper from lower case in the first column,
+    // and the last few columns have too few samples. Fill those in by
+    // hand, from guesses on the plain text.
+    analyzer.set_plain_byte(0, 0, b'I');
+    for (index, byte) in "his turn,".bytes().enumerate() {
+        analyzer.set_plain_byte(37, 29 + index, byte);
+    }
+
+    println!("Challenge 19 :");
+    for plain_bytes in analyzer.plain_texts() {
+        println!("{}", String::from_utf8_lossy(&plain_bytes));
+    }
+}
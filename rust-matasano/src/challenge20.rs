@@ -0,0 +1,43 @@
+use rustc_serialize::hex::ToHex;
+
+use libs::utils;
+use libs::random::Random;
+use libs::cryptor::Encryptor;
+use libs::cryptor::Aes128CtrCryptor;
+use libs::cryptor::CounterFormat;
+use libs::analyzer::FixedNonceCtrAnalyzer;
+
+pub fn run() {
+    let plain_texts = utils::from_base64_lines("../resources/20.txt");
+    let key = &mut [0; 16];
+    Random::new().fill_bytes(key);
+
+    let nonce = &[0; 8];
+    let cryptor = Aes128CtrCryptor::new(nonce, CounterFormat::LittleEndian64);
+
+    let cipher_texts = plain_texts.iter()
+        .map(|plain_bytes| cryptor.encrypt(plain_bytes, key)
+            .expect("Encryption failed"))
+        .collect::<Vec<Vec<u8>>>();
+
+    // Truncated to the shortest cipher text, every column gets a sample
+    // from every line, which is enough for statistics alone
+    let mut analyzer = FixedNonceCtrAnalyzer::truncated(cipher_texts);
+
+    println!("Challenge 20 :");
+    println!("Keystream : {}", analyzer.keystream().to_hex());
+    for index in analyzer.low_confidence_columns(0.1) {
+        println!("Low confidence in column {} : {}", index,
+            analyzer.confidence()[index]);
+    }
+
+    // Every line starts with a capital, which letter frequencies can't
+    // tell from lower case, and that throws off the next column too. Fix
+    // both by hand, from the first line.
+    analyzer.set_plain_byte(0, 0, b'F');
+    analyzer.set_plain_byte(0, 1, b'o');
+
+    for plain_bytes in analyzer.plain_texts() {
+        println!("{}", String::from_utf8_lossy(&plain_bytes));
+    }
+}
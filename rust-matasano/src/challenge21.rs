@@ -0,0 +1,32 @@
+use libs::mt19937::{Mt19937, Mt19937_64};
+use libs::random::Random;
+
+pub fn run() {
+    let mut mt = Mt19937::new(5489);
+    let outputs = (0..5).map(|_| mt.extract().to_string())
+        .collect::<Vec<String>>();
+
+    println!("Challenge 21 : {}", outputs.join(", "));
+
+    // Same as the start of mt19937ar.out and mt19937-64.out
+    let mut mt = Mt19937::from_array(&[0x123, 0x234, 0x345, 0x456]);
+    let outputs = (0..5).map(|_| mt.extract().to_string())
+        .collect::<Vec<String>>();
+
+    println!("Seeded by array : {}", outputs.join(", "));
+
+    let mut mt = Mt19937_64::from_array(&[0x12345, 0x23456, 0x34567,
+        0x45678]);
+    let outputs = (0..5).map(|_| mt.extract().to_string())
+        .collect::<Vec<String>>();
+
+    println!("64 bit, seeded by array : {}", outputs.join(", "));
+
+    // A seeded Random replays the same run
+    let replays = [Random::mt19937(21), Random::mt19937(21),
+        Random::mt19937_64(21), Random::mt19937_64(21)].iter_mut()
+        .map(|random| random.rand_range(&0, &1000).to_string())
+        .collect::<Vec<String>>();
+
+    println!("Replayed : {}", replays.join(", "));
+}
@@ -0,0 +1,27 @@
+use libs::analyzer;
+use libs::clock::Clock;
+use libs::clock::SystemClock;
+use libs::oracle::TimeSeededOracle;
+use libs::random::Random;
+
+pub fn run() {
+    let clock = SystemClock;
+    let mut random = Random::new();
+    let oracle = TimeSeededOracle::new(&clock);
+
+    // A few seconds rather than 40 to 1000, to keep the run short. The
+    // search window covers either.
+    clock.sleep(random.rand_range(&1, &4) as u32);
+    let output = oracle.rand();
+    clock.sleep(random.rand_range(&1, &4) as u32);
+
+    let seed = analyzer::recover_time_seed(output, clock.now(), 2000)
+        .expect("Seed not found");
+    println!("Challenge 22 : {}", seed);
+
+    // A password reset token drawn the same way gives its seed up too
+    let token = oracle.reset_token(16);
+    let seed = analyzer::recover_token_seed(&token, clock.now(), 2000)
+        .expect("Seed not found");
+    println!("Reset token seed : {}", seed);
+}
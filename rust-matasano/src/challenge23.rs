@@ -0,0 +1,22 @@
+use libs::analyzer;
+use libs::mt19937;
+use libs::mt19937::Mt19937;
+use libs::random::Random;
+
+pub fn run() {
+    let mut random = Random::new();
+    let mut mt = Mt19937::new(random.rand() as u32);
+
+    // Tap into the stream somewhere other than a block boundary
+    for _ in 0..random.rand_range(&0, &1000) {
+        mt.extract();
+    }
+
+    let outputs = (0..mt19937::N).map(|_| mt.extract())
+        .collect::<Vec<u32>>();
+    let mut cloned = analyzer::clone_mt19937(&outputs)
+        .expect("Cloning failed");
+
+    let predicted = (0..1000).all(|_| cloned.extract() == mt.extract());
+    println!("Challenge 23 : {}", predicted);
+}
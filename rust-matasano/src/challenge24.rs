@@ -0,0 +1,22 @@
+use libs::analyzer;
+use libs::clock::Clock;
+use libs::clock::SystemClock;
+use libs::oracle::Oracle;
+use libs::oracle::TimeSeededOracle;
+
+pub fn run() {
+    let oracle = Oracle::random_prefix(None);
+    let known = [b'A'; 14];
+
+    let cipher_bytes = oracle.encrypt_mt19937(&known)
+        .expect("Encryption failed");
+    let key = analyzer::recover_mt19937_key(&cipher_bytes, &known)
+        .expect("Key not found");
+    println!("Challenge 24 : Key {:02x}{:02x}", key[0], key[1]);
+
+    let clock = SystemClock;
+    let token = TimeSeededOracle::new(&clock).reset_token(16);
+    let time_seeded = analyzer::is_time_seeded_token(&token, clock.now(),
+        3600);
+    println!("Challenge 24 : Time seeded token {}", time_seeded);
+}
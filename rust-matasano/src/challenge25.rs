@@ -0,0 +1,22 @@
+use libs::utils;
+use libs::analyzer;
+use libs::oracle::Oracle;
+use libs::cryptor::Decryptor;
+use libs::cryptor::Aes128EcbDecryptor;
+
+pub fn run() {
+    let bytes = utils::from_base64_file("../resources/7.txt");
+    let key = &String::from("YELLOW SUBMARINE").into_bytes();
+    let plain_bytes = Aes128EcbDecryptor.decrypt(&bytes, key)
+        .expect("Decryption failed");
+
+    let oracle = Oracle::new(None, None);
+    let cipher_bytes = oracle.encrypt_ctr(&plain_bytes)
+        .expect("Encryption failed");
+
+    let result = analyzer::analyze_ctr_edit(&cipher_bytes,
+        |cipher_bytes, offset, new_bytes| {
+            oracle.edit(cipher_bytes, offset, new_bytes)
+        }).expect("Analysis failed");
+    println!("Challenge 25 : {}", String::from_utf8(result).unwrap());
+}
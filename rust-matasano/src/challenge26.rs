@@ -0,0 +1,7 @@
+use challenge16;
+use libs::oracle::CipherMode;
+
+pub fn run() {
+    println!("Challenge 26 : isAdmin : {}",
+        challenge16::is_admin(CipherMode::Ctr));
+}
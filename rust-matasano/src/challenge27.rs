@@ -0,0 +1,19 @@
+use libs::analyzer;
+use libs::oracle::Oracle;
+use libs::cryptor::Decryptor;
+use libs::cryptor::Aes128CbcDecryptor;
+
+pub fn run() {
+    let oracle = Oracle::key_as_iv(None, None);
+    let message = "Three blocks of message, long enough to forge with";
+    let cipher_bytes = oracle.encrypt_cbc(message.as_bytes())
+        .expect("Encryption failed");
+
+    let key = analyzer::recover_key_as_iv(&cipher_bytes, |cipher_bytes| {
+        oracle.decrypt_cbc_ascii(cipher_bytes)
+    }).expect("Key not found");
+
+    let result = Aes128CbcDecryptor(&key).decrypt(&cipher_bytes, &key)
+        .expect("Decryption failed");
+    println!("Challenge 27 : {}", String::from_utf8(result).unwrap());
+}
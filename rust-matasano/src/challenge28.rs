@@ -0,0 +1,13 @@
+use rustc_serialize::hex::ToHex;
+
+use libs::oracle::MacOracle;
+
+pub fn run() {
+    let mac_oracle = MacOracle::sha1();
+    let message = b"YELLOW SUBMARINE";
+    let mac = mac_oracle.mac(message);
+
+    let tampered = !mac_oracle.verify(b"YELLOW SUBMARINA", &mac);
+    println!("Challenge 28 : {} {}", mac.to_hex(),
+        mac_oracle.verify(message, &mac) && tampered);
+}
@@ -0,0 +1,16 @@
+use libs::analyzer;
+use libs::oracle::MacOracle;
+
+pub fn run() {
+    let mac_oracle = MacOracle::md4();
+    let message = b"comment1=cooking%20MCs;userdata=foo;\
+        comment2=%20like%20a%20pound%20of%20bacon";
+    let mac = mac_oracle.mac(message);
+
+    let (forged, forged_mac) = analyzer::forge_md4_mac(message, &mac,
+        b";admin=true", 32, |message, mac| mac_oracle.verify(message, mac))
+        .expect("Forging failed");
+
+    println!("Challenge 30 : {} {}", String::from_utf8_lossy(&forged),
+        mac_oracle.verify(&forged, &forged_mac));
+}
@@ -0,0 +1,31 @@
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rustc_serialize::hex::ToHex;
+
+use libs::analyzer;
+use libs::http;
+use libs::oracle::HmacOracle;
+
+pub fn run() {
+    // Same server as the timing-server binary, run in process. With 50ms
+    // a byte a whole MAC takes hours, so this goes for the first few
+    // bytes under a shorter delay.
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Can't bind");
+    let addr = listener.local_addr().unwrap();
+    let hmac_oracle = Arc::new(HmacOracle::new(Duration::from_millis(5)));
+
+    let server_oracle = hmac_oracle.clone();
+    thread::spawn(move || http::serve_hmac(&listener, &server_oracle));
+
+    let mac = analyzer::recover_mac_naive(20, 2, |signature| {
+        let target = format!("/test?file=foo&signature={}",
+            signature.to_hex());
+        http::get(addr, &target).expect("Request failed") == 200
+    });
+
+    println!("Challenge 31 : {} {}", mac.to_hex(),
+        hmac_oracle.hmac(b"foo").to_hex());
+}
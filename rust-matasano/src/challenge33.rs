@@ -0,0 +1,37 @@
+use rustc_serialize::hex::ToHex;
+
+use libs::bignum::BigUint;
+use libs::cryptor::{Aes128CbcDecryptor, Aes128CbcEncryptor, Decryptor,
+    Encryptor};
+use libs::dh;
+use libs::dh::Group;
+use libs::random::Random;
+use libs::sha256::Sha256;
+
+pub fn run() {
+    let mut random = Random::new();
+
+    // Small numbers first, then the real thing
+    let small = Group::new(BigUint::from(37u32), BigUint::from(5u32));
+    let a = small.keypair(&mut random);
+    let b = small.keypair(&mut random);
+    let small_agreed = small.shared_secret(&a.private, &b.public) ==
+        small.shared_secret(&b.private, &a.public);
+
+    let group = Group::modp_1536();
+    let a = group.keypair(&mut random);
+    let b = group.keypair(&mut random);
+    let a_key = dh::aes_key::<Sha256>(
+        &group.shared_secret(&a.private, &b.public));
+    let b_key = dh::aes_key::<Sha256>(
+        &group.shared_secret(&b.private, &a.public));
+
+    let iv = &mut [0; 16];
+    random.fill_bytes(iv);
+    let cipher = Aes128CbcEncryptor(iv)
+        .encrypt(b"YELLOW SUBMARINE", &a_key).unwrap();
+    let plain = Aes128CbcDecryptor(iv).decrypt(&cipher, &b_key).unwrap();
+
+    println!("Challenge 33 : {} {} {}", a_key.to_hex(), small_agreed,
+        String::from_utf8(plain).unwrap());
+}
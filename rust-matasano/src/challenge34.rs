@@ -0,0 +1,20 @@
+use libs::mitm::KeyInjection;
+use libs::dh::Group;
+use libs::protocol;
+use libs::protocol::Relay;
+
+pub fn run() {
+    let messages = vec![b"Hello,".to_vec(), b"Bob".to_vec()];
+
+    // An honest relay first, for Bob's side to compare with
+    let honest = protocol::run_session(Group::modp_1536(), &messages,
+        &mut Relay).expect("Session failed");
+
+    let mut key_injection = KeyInjection::new();
+    let transcript = protocol::run_session(Group::modp_1536(), &messages,
+        &mut key_injection).expect("Session failed");
+
+    println!("Challenge 34 : {} {} {}",
+        String::from_utf8(key_injection.plains.join(&b' ')).unwrap(),
+        transcript.echoes == messages, transcript.received == honest.received);
+}
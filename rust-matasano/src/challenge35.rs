@@ -0,0 +1,19 @@
+use libs::mitm::{FakeGenerator, GroupInjection};
+use libs::dh::Group;
+use libs::protocol;
+
+pub fn run() {
+    let messages = vec![b"Hello, Bob".to_vec()];
+
+    let results = [FakeGenerator::One, FakeGenerator::P,
+        FakeGenerator::PMinusOne].iter().map(|&generator| {
+
+        let mut group_injection = GroupInjection::new(generator);
+        let transcript = protocol::run_session(Group::modp_1536(),
+            &messages, &mut group_injection).expect("Session failed");
+
+        group_injection.plains == messages && transcript.echoes == messages
+    }).collect::<Vec<bool>>();
+
+    println!("Challenge 35 : {:?}", results);
+}
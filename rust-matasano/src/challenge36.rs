@@ -0,0 +1,27 @@
+use std::thread;
+
+use libs::dh::Group;
+use libs::protocol;
+use libs::srp;
+use libs::srp::{SrpClient, SrpServer};
+
+pub fn run() {
+    let email = "alice@example.com";
+    let mut server = SrpServer::new(Group::modp_1536());
+    server.register(email, b"correct horse battery staple");
+
+    let results = [&b"correct horse battery staple"[..], b"hunter2"].iter()
+        .map(|password| {
+            let (server_end, client_end) = protocol::connect();
+            let mut client = SrpClient::new(Group::modp_1536(), email,
+                password);
+
+            thread::scope(|scope| {
+                let server = &mut server;
+                scope.spawn(move || srp::serve(server, &server_end));
+                srp::login(&mut client, &client_end).expect("Login failed")
+            })
+        }).collect::<Vec<bool>>();
+
+    println!("Challenge 36 : {:?}", results);
+}
@@ -0,0 +1,27 @@
+use std::thread;
+
+use libs::dh::Group;
+use libs::protocol;
+use libs::srp;
+use libs::srp::SrpServer;
+
+pub fn run() {
+    let email = "alice@example.com";
+    let mut server = SrpServer::unchecked(Group::modp_1536());
+    server.register(email, b"correct horse battery staple");
+
+    // A of 0, N and 2N, no password needed
+    let results = (0..3u32).map(|multiple| {
+        let public = Group::modp_1536().p * multiple;
+        let (server_end, client_end) = protocol::connect();
+
+        thread::scope(|scope| {
+            let server = &mut server;
+            scope.spawn(move || srp::serve(server, &server_end));
+            srp::zero_key_login(&client_end, email, public)
+                .expect("Login failed")
+        })
+    }).collect::<Vec<bool>>();
+
+    println!("Challenge 37 : {:?}", results);
+}
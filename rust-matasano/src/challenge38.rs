@@ -0,0 +1,58 @@
+use std::io;
+use std::io::Write;
+use std::thread;
+use std::thread::JoinHandle;
+
+use libs::dh::Group;
+use libs::protocol;
+use libs::protocol::Endpoint;
+use libs::random::Random;
+use libs::simple_srp;
+use libs::simple_srp::{SimpleSrpClient, SimpleSrpMessage, SimpleSrpServer};
+use libs::utils;
+
+const EMAIL: &str = "alice@example.com";
+
+pub fn run() {
+    let words = utils::read_lines("../resources/38.txt");
+    let mut random = Random::new();
+    let password = words[random.rand_range(&0, &(words.len() as i32))
+        as usize].clone();
+
+    // Alice logs in to the real server first, to show the password works
+    let mut server = SimpleSrpServer::new(Group::modp_1536(), EMAIL,
+        password.as_bytes());
+    let (server_end, client_end) = protocol::connect();
+    let client_thread = client_login(client_end, &password);
+    let served = simple_srp::serve(&mut server, &server_end)
+        .expect("Serve failed");
+    let honest = client_thread.join().unwrap() && served;
+
+    // Then Mallory takes the place of the server
+    let (server_end, client_end) = protocol::connect();
+    let client_thread = client_login(client_end, &password);
+    let captured = simple_srp::capture_login(&server_end,
+        &Group::modp_1536()).expect("Capture failed");
+    client_thread.join().unwrap();
+
+    let cracked = simple_srp::crack_password(&Group::modp_1536(),
+        &captured, &words, 4, |tried, total| {
+            print!("\rChallenge 38 : {}/{}", tried, total);
+            io::stdout().flush().unwrap();
+        });
+
+    // Padded to cover the progress count
+    println!("\rChallenge 38 : {} {} {:<16}", honest, captured.email,
+        format!("{:?}", cracked));
+}
+
+fn client_login(endpoint: Endpoint<SimpleSrpMessage>, password: &str)
+    -> JoinHandle<bool> {
+
+    let password = password.to_string();
+    thread::spawn(move || {
+        let mut client = SimpleSrpClient::new(Group::modp_1536(), EMAIL,
+            password.as_bytes());
+        simple_srp::login(&mut client, &endpoint).expect("Login failed")
+    })
+}
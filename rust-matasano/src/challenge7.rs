@@ -7,7 +7,8 @@ pub fn run() {
     let key = &String::from("YELLOW SUBMARINE").into_bytes();
     let decryptor = Aes128EcbDecryptor;
 
-    let result = decryptor.decrypt(&bytes, key);
+    let result = decryptor.decrypt(&bytes, key)
+        .expect("Decryption failed");
     println!("Challenge 7 : {}", String::from_utf8(result).unwrap());
 }
 
@@ -1,7 +0,0 @@
-pub mod utils;
-pub mod cryptor;
-pub mod pkcs7;
-pub mod random;
-pub mod analyzer;
-pub mod oracle;
-pub mod keysize;
@@ -0,0 +1,489 @@
+// The key size and round keys are exposed for attacks that no challenge
+// runs
+#![allow(dead_code)]
+
+use libs::cryptor::BlockCipher;
+use libs::cryptor::CryptoError;
+
+// FIPS-197 section 5.1.1
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5,
+    0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0,
+    0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc,
+    0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a,
+    0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0,
+    0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b,
+    0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85,
+    0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5,
+    0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17,
+    0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88,
+    0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c,
+    0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9,
+    0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6,
+    0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e,
+    0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94,
+    0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68,
+    0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38,
+    0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87,
+    0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d,
+    0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2,
+    0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16,
+    0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda,
+    0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a,
+    0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02,
+    0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea,
+    0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85,
+    0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89,
+    0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20,
+    0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31,
+    0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d,
+    0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0,
+    0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26,
+    0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+// Round constants for the key expansion, FIPS-197 section 5.2
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80,
+    0x1b, 0x36];
+
+// AES state is 16 bytes laid out column by column, the same order in
+// which the input block is read. Byte at row r and column c is at
+// index r + 4 * c.
+pub type State = [u8; 16];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AesKeySize {
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+impl AesKeySize {
+    pub fn from_key(key: &[u8]) -> Result<AesKeySize, CryptoError> {
+        match key.len() {
+            16 => Ok(AesKeySize::Aes128),
+            24 => Ok(AesKeySize::Aes192),
+            32 => Ok(AesKeySize::Aes256),
+            len => Err(CryptoError::InvalidKeyLength(len))
+        }
+    }
+
+    pub fn key_len(&self) -> usize {
+        match *self {
+            AesKeySize::Aes128 => 16,
+            AesKeySize::Aes192 => 24,
+            AesKeySize::Aes256 => 32,
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        match *self {
+            AesKeySize::Aes128 => 10,
+            AesKeySize::Aes192 => 12,
+            AesKeySize::Aes256 => 14,
+        }
+    }
+}
+
+pub struct Aes {
+    key_size: AesKeySize,
+    round_keys: Vec<State>,
+}
+
+impl Aes {
+    pub fn new(key: &[u8]) -> Result<Aes, CryptoError> {
+        let key_size = AesKeySize::from_key(key)?;
+        Aes::with_key_size(key, key_size)
+    }
+
+    pub fn with_key_size(key: &[u8], key_size: AesKeySize)
+        -> Result<Aes, CryptoError> {
+
+        if key.len() != key_size.key_len() {
+            return Err(CryptoError::InvalidKeyLength(key.len()));
+        }
+
+        Ok(Aes { key_size, round_keys: expand_key(key, &key_size) })
+    }
+
+    pub fn key_size(&self) -> AesKeySize {
+        self.key_size
+    }
+
+    pub fn round_keys(&self) -> &[State] {
+        &self.round_keys
+    }
+
+    // Same as encrypt_block, but calls the hook with the round number
+    // and the state at the end of each round. Round 0 is the initial
+    // AddRoundKey. The hook may change the state to inject faults.
+    pub fn encrypt_block_with<F>(&self, block: &State, mut hook: F) -> State
+        where F: FnMut(usize, &mut State) {
+
+        let rounds = self.key_size.rounds();
+        let mut state = *block;
+
+        add_round_key(&mut state, &self.round_keys[0]);
+        hook(0, &mut state);
+
+        for round in 1..rounds {
+            sub_bytes(&mut state);
+            shift_rows(&mut state);
+            mix_columns(&mut state);
+            add_round_key(&mut state, &self.round_keys[round]);
+            hook(round, &mut state);
+        }
+
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        add_round_key(&mut state, &self.round_keys[rounds]);
+        hook(rounds, &mut state);
+
+        state
+    }
+
+    // Equivalent of encrypt_block_with for decryption. Rounds are
+    // numbered in the order they run, so round 0 undoes the last
+    // encryption round.
+    pub fn decrypt_block_with<F>(&self, block: &State, mut hook: F) -> State
+        where F: FnMut(usize, &mut State) {
+
+        let rounds = self.key_size.rounds();
+        let mut state = *block;
+
+        add_round_key(&mut state, &self.round_keys[rounds]);
+        hook(0, &mut state);
+
+        for round in 1..rounds {
+            inv_shift_rows(&mut state);
+            inv_sub_bytes(&mut state);
+            add_round_key(&mut state, &self.round_keys[rounds - round]);
+            inv_mix_columns(&mut state);
+            hook(round, &mut state);
+        }
+
+        inv_shift_rows(&mut state);
+        inv_sub_bytes(&mut state);
+        add_round_key(&mut state, &self.round_keys[0]);
+        hook(rounds, &mut state);
+
+        state
+    }
+}
+
+impl BlockCipher<16> for Aes {
+    fn encrypt_block(&self, block: &State) -> State {
+        self.encrypt_block_with(block, |_, _| {})
+    }
+
+    fn decrypt_block(&self, block: &State) -> State {
+        self.decrypt_block_with(block, |_, _| {})
+    }
+}
+
+fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    [SBOX[word[0] as usize], SBOX[word[1] as usize],
+        SBOX[word[2] as usize], SBOX[word[3] as usize]]
+}
+
+fn rot_word(word: [u8; 4]) -> [u8; 4] {
+    [word[1], word[2], word[3], word[0]]
+}
+
+fn expand_key(key: &[u8], key_size: &AesKeySize) -> Vec<State> {
+    let nk = key_size.key_len() / 4;
+    let total_words = 4 * (key_size.rounds() + 1);
+    let mut words: Vec<[u8; 4]> = key.chunks(4)
+        .map(|chunk| [chunk[0], chunk[1], chunk[2], chunk[3]])
+        .collect();
+
+    for i in nk..total_words {
+        let mut temp = words[i - 1];
+
+        if i % nk == 0 {
+            temp = sub_word(rot_word(temp));
+            temp[0] ^= RCON[i / nk - 1];
+        } else if nk > 6 && i % nk == 4 {
+            temp = sub_word(temp);
+        }
+
+        let prev = words[i - nk];
+        words.push([prev[0] ^ temp[0], prev[1] ^ temp[1],
+            prev[2] ^ temp[2], prev[3] ^ temp[3]]);
+    }
+
+    words.chunks(4)
+        .map(|round_words| {
+            let mut round_key = [0; 16];
+            for (i, word) in round_words.iter().enumerate() {
+                round_key[4 * i..4 * i + 4].copy_from_slice(word);
+            }
+            round_key
+        })
+        .collect()
+}
+
+// Multiplication by x in GF(2^8)
+fn xtime(byte: u8) -> u8 {
+    match byte & 0x80 {
+        0 => byte << 1,
+        _ => (byte << 1) ^ 0x1b
+    }
+}
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+
+    product
+}
+
+pub fn add_round_key(state: &mut State, round_key: &State) {
+    for (byte, key_byte) in state.iter_mut().zip(round_key.iter()) {
+        *byte ^= key_byte;
+    }
+}
+
+pub fn sub_bytes(state: &mut State) {
+    for byte in state.iter_mut() {
+        *byte = SBOX[*byte as usize];
+    }
+}
+
+pub fn inv_sub_bytes(state: &mut State) {
+    for byte in state.iter_mut() {
+        *byte = INV_SBOX[*byte as usize];
+    }
+}
+
+// Row r is rotated left by r positions
+pub fn shift_rows(state: &mut State) {
+    let old = *state;
+
+    for row in 1..4 {
+        for column in 0..4 {
+            state[row + 4 * column] = old[row + 4 * ((column + row) % 4)];
+        }
+    }
+}
+
+pub fn inv_shift_rows(state: &mut State) {
+    let old = *state;
+
+    for row in 1..4 {
+        for column in 0..4 {
+            state[row + 4 * ((column + row) % 4)] = old[row + 4 * column];
+        }
+    }
+}
+
+pub fn mix_columns(state: &mut State) {
+    for column in state.chunks_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+
+        column[0] = xtime(a0) ^ (xtime(a1) ^ a1) ^ a2 ^ a3;
+        column[1] = a0 ^ xtime(a1) ^ (xtime(a2) ^ a2) ^ a3;
+        column[2] = a0 ^ a1 ^ xtime(a2) ^ (xtime(a3) ^ a3);
+        column[3] = (xtime(a0) ^ a0) ^ a1 ^ a2 ^ xtime(a3);
+    }
+}
+
+pub fn inv_mix_columns(state: &mut State) {
+    for column in state.chunks_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+
+        column[0] = gf_mul(a0, 0x0e) ^ gf_mul(a1, 0x0b) ^ gf_mul(a2, 0x0d)
+            ^ gf_mul(a3, 0x09);
+        column[1] = gf_mul(a0, 0x09) ^ gf_mul(a1, 0x0e) ^ gf_mul(a2, 0x0b)
+            ^ gf_mul(a3, 0x0d);
+        column[2] = gf_mul(a0, 0x0d) ^ gf_mul(a1, 0x09) ^ gf_mul(a2, 0x0e)
+            ^ gf_mul(a3, 0x0b);
+        column[3] = gf_mul(a0, 0x0b) ^ gf_mul(a1, 0x0d) ^ gf_mul(a2, 0x09)
+            ^ gf_mul(a3, 0x0e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::hex::FromHex;
+    use crypto::aessafe::{ AesSafe128Encryptor, AesSafe192Encryptor,
+        AesSafe256Encryptor};
+    use crypto::symmetriccipher::BlockEncryptor;
+    use libs::random::Random;
+
+    fn to_block(hex: &str) -> State {
+        let mut block = [0; 16];
+        block.copy_from_slice(&hex.from_hex().unwrap());
+        block
+    }
+
+    #[test]
+    fn test_sbox_is_inverse() {
+        for byte in 0..256 {
+            assert_eq!(INV_SBOX[SBOX[byte] as usize] as usize, byte);
+        }
+    }
+
+    #[test]
+    fn test_gf_mul() {
+        // FIPS-197 section 4.2
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_mul(0x57, 0x13), 0xfe);
+    }
+
+    #[test]
+    fn test_mix_columns_round_trip() {
+        let mut state = to_block("000102030405060708090a0b0c0d0e0f");
+        mix_columns(&mut state);
+        inv_mix_columns(&mut state);
+        assert_eq!(state, to_block("000102030405060708090a0b0c0d0e0f"));
+    }
+
+    #[test]
+    fn test_shift_rows() {
+        let mut state = to_block("000102030405060708090a0b0c0d0e0f");
+        shift_rows(&mut state);
+        assert_eq!(state, to_block("00050a0f04090e03080d02070c01060b"));
+
+        inv_shift_rows(&mut state);
+        assert_eq!(state, to_block("000102030405060708090a0b0c0d0e0f"));
+    }
+
+    #[test]
+    fn test_expand_key() {
+        // FIPS-197, Appendix A.1
+        let aes = Aes::new(&"2b7e151628aed2a6abf7158809cf4f3c".from_hex()
+            .unwrap()).unwrap();
+
+        let round_keys = aes.round_keys();
+        assert_eq!(round_keys.len(), 11);
+        assert_eq!(round_keys[1],
+            to_block("a0fafe1788542cb123a339392a6c7605"));
+        assert_eq!(round_keys[10],
+            to_block("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+    }
+
+    #[test]
+    fn test_encrypt_block_rounds() {
+        // FIPS-197, Appendix B
+        let aes = Aes::new(&"2b7e151628aed2a6abf7158809cf4f3c".from_hex()
+            .unwrap()).unwrap();
+        let block = to_block("3243f6a8885a308d313198a2e0370734");
+        let mut states = Vec::new();
+
+        let cipher_block = aes.encrypt_block_with(&block, |_, state| {
+            states.push(*state)
+        });
+
+        assert_eq!(cipher_block, to_block("3925841d02dc09fbdc118597196a0b32"));
+        assert_eq!(states.len(), 11);
+        assert_eq!(states[0], to_block("193de3bea0f4e22b9ac68d2ae9f84808"));
+        assert_eq!(states[1], to_block("a49c7ff2689f352b6b5bea43026a5049"));
+    }
+
+    #[test]
+    fn test_encrypt_block_fault_injection() {
+        let aes = Aes::new(&[0; 16]).unwrap();
+        let block = [0; 16];
+        let cipher_block = aes.encrypt_block(&block);
+
+        // Flipping a bit just before the last round only touches a
+        // single byte of the output, as there is no MixColumns in it
+        let faulty_block = aes.encrypt_block_with(&block, |round, state| {
+            if round == 9 {
+                state[0] ^= 1;
+            }
+        });
+
+        let diff_count = cipher_block.iter()
+            .zip(faulty_block.iter())
+            .filter(|&(a, b)| a != b)
+            .count();
+        assert_eq!(diff_count, 1);
+    }
+
+    #[test]
+    fn test_decrypt_block() {
+        let plain_block = to_block("00112233445566778899aabbccddeeff");
+        let keys = [
+            "000102030405060708090a0b0c0d0e0f",
+            "000102030405060708090a0b0c0d0e0f1011121314151617",
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        ];
+
+        for key in keys.iter() {
+            let aes = Aes::new(&key.from_hex().unwrap()).unwrap();
+            let cipher_block = aes.encrypt_block(&plain_block);
+            assert_eq!(aes.decrypt_block(&cipher_block), plain_block);
+        }
+    }
+
+    #[test]
+    fn test_cross_check_rust_crypto() {
+        let mut random = Random::new();
+
+        for &key_len in [16, 24, 32].iter() {
+            for _ in 0..20 {
+                let mut key = vec![0; key_len];
+                let mut block = [0; 16];
+                random.fill_bytes(&mut key);
+                random.fill_bytes(&mut block);
+
+                let expected = &mut [0; 16];
+                match key_len {
+                    16 => AesSafe128Encryptor::new(&key)
+                        .encrypt_block(&block, expected),
+                    24 => AesSafe192Encryptor::new(&key)
+                        .encrypt_block(&block, expected),
+                    _ => AesSafe256Encryptor::new(&key)
+                        .encrypt_block(&block, expected),
+                }
+
+                let aes = Aes::new(&key).unwrap();
+                assert_eq!(&aes.encrypt_block(&block), expected);
+            }
+        }
+    }
+}
@@ -1,7 +1,23 @@
+use std::cell::Cell;
+use std::io;
+use std::time::{Duration, Instant};
 use std::str::from_utf8;
 
 use libs::pkcs7::Pkcs7Pad;
 use libs::oracle::Oracle;
+use libs::oracle::CipherMode;
+use libs::cryptor::CryptoError;
+use libs::cryptor::xor;
+use libs::cryptor::Decryptor;
+use libs::cryptor::Mt19937Cryptor;
+use libs::pkcs7;
+use libs::english;
+use libs::mt19937;
+use libs::mt19937::Mt19937;
+use libs::sha1;
+use libs::sha1::Sha1;
+use libs::md4;
+use libs::md4::Md4;
 
 pub struct OracleAnalyzer {
     oracle: Oracle,
@@ -13,20 +29,20 @@ impl OracleAnalyzer {
     }
 
     pub fn analyze_ecb(&self) -> Result<String, AnalyzerError> {
-        let keysize = self.find_keysize();
+        let keysize = self.find_keysize()?;
         self.assert_ecb(&keysize)?;
-        let prefix_len = self.find_prefix_len(&keysize);
+        let prefix_len = self.find_prefix_len(&keysize)?;
 
         // OracleWrapper will take care of the prefix
         let oracle_wrapper = 
             OracleWrapper::new(&self.oracle, &keysize, prefix_len);
 
-        let cipher_bytes = oracle_wrapper.encrypt(&[]);
+        let cipher_bytes = oracle_wrapper.encrypt(&[])?;
         let cipher_bytes_len = cipher_bytes.len();
         let mut plain_bytes = Vec::new();
 
         for _ in 0..cipher_bytes_len {
-            let next_byte = oracle_wrapper.ecb_next_byte(&plain_bytes);
+            let next_byte = oracle_wrapper.ecb_next_byte(&plain_bytes)?;
 
             // ecb_next_byte logic will start failing after it has found the
             // first byte of the padding
@@ -39,14 +55,17 @@ impl OracleAnalyzer {
         self.fix_padding(&mut plain_bytes, &keysize, &cipher_bytes_len);
 
         //Final validation
-        if !oracle_wrapper.encrypt(&plain_bytes)
+        if !oracle_wrapper.encrypt(&plain_bytes)?
             .iter()
             .take(cipher_bytes.len())
             .eq(cipher_bytes.iter()) {
                 Err(AnalyzerError::UnknownError)?;
             }
 
-        from_utf8(&plain_bytes.pkcs7_unpad().expect("Unpad Error"))
+        let unpadded_bytes = plain_bytes.pkcs7_unpad()
+            .map_err(CryptoError::from)?;
+
+        from_utf8(&unpadded_bytes)
             .map(|plain_text| {
                 String::from(plain_text)
             }).map_err(|_| {
@@ -71,8 +90,10 @@ impl OracleAnalyzer {
         }
     }
 
-    fn find_prefix_len(&self, keysize: &usize) -> usize {
-        let prefix_block_id = self.find_prefix_block_id(keysize);
+    fn find_prefix_len(&self, keysize: &usize)
+        -> Result<usize, AnalyzerError> {
+
+        let prefix_block_id = self.find_prefix_block_id(keysize)?;
         let prefix_range = prefix_block_id * keysize..
             (prefix_block_id + 1) * keysize;
         let mut input_str = String::new();
@@ -81,7 +102,7 @@ impl OracleAnalyzer {
         // prefix_block is the ending block of the prefix.
         // Here, we are trying to find the length of prefix part in this block.
         loop {
-            let cipher_bytes = self.oracle.encrypt(input_str.as_bytes());
+            let cipher_bytes = self.oracle.encrypt(input_str.as_bytes())?;
             let prefix_block = &cipher_bytes[prefix_range.clone()].to_vec();
 
             if prev_prefix_block.is_some() &&
@@ -94,32 +115,34 @@ impl OracleAnalyzer {
             prev_prefix_block = Some(prefix_block.to_vec());
         }
 
-        ((prefix_block_id + 1) * keysize)  - (input_str.len() - 1)
+        Ok(((prefix_block_id + 1) * keysize)  - (input_str.len() - 1))
 
     }
 
-    fn find_prefix_block_id(&self, keysize: &usize) -> usize {
-        let cipher_a = self.oracle.encrypt("A".as_bytes());
-        let cipher_b = self.oracle.encrypt("B".as_bytes());
+    fn find_prefix_block_id(&self, keysize: &usize)
+        -> Result<usize, AnalyzerError> {
+
+        let cipher_a = self.oracle.encrypt("A".as_bytes())?;
+        let cipher_b = self.oracle.encrypt("B".as_bytes())?;
 
         let zip_list = cipher_a.chunks(keysize.clone()).zip(
             cipher_b.chunks(keysize.clone()));
 
         // First mismatching block will be the prefix block
-        zip_list.enumerate()
+        Ok(zip_list.enumerate()
             .find(|&(_, (block1, block2))| !block1.iter().eq(block2.iter()))
             .map(|(prefix_block, (_, _))| prefix_block)
-            .unwrap()
+            .unwrap())
     }
 
-    fn find_keysize(&self) -> usize {
+    fn find_keysize(&self) -> Result<usize, AnalyzerError> {
         let mut input = String::from("A");
-        let initial_size = self.oracle.encrypt(input.as_bytes()).len();
+        let initial_size = self.oracle.encrypt(input.as_bytes())?.len();
         let keysize;
 
         loop {
             input.push('A');
-            let output_size = self.oracle.encrypt(input.as_bytes()).len();
+            let output_size = self.oracle.encrypt(input.as_bytes())?.len();
 
             if output_size != initial_size {
                 keysize = output_size - initial_size;
@@ -127,7 +150,7 @@ impl OracleAnalyzer {
             }
         }
 
-        keysize
+        Ok(keysize)
     }
 
     fn assert_ecb(&self, keysize: &usize) -> Result<(), AnalyzerError> {
@@ -139,7 +162,7 @@ impl OracleAnalyzer {
             input.push('A');
         }
 
-        let cipher_text = self.oracle.encrypt(input.as_bytes());
+        let cipher_text = self.oracle.encrypt(input.as_bytes())?;
 
         if !is_ecb(&cipher_text, keysize) {
             Err(AnalyzerError::NotEcb)
@@ -176,26 +199,30 @@ impl<'a> OracleWrapper<'a> {
             keysize: *keysize, prefix_block_len: (prefix_len + prefix_fill_len) }
     }
 
-    fn encrypt(&self, plain_bytes: &[u8]) -> Vec<u8> {
+    fn encrypt(&self, plain_bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
         let mut input = self.prefix_fill.clone();
         input.extend(plain_bytes);
 
-        self.oracle.encrypt(&input)
+        Ok(self.oracle.encrypt(&input)?
             .iter()
             .skip(self.prefix_block_len)
             .cloned()
-            .collect()
+            .collect())
     }
 
-    fn encrypt_take(&self, plain_bytes: &[u8], len: &usize) -> Vec<u8>{
-        self.encrypt(plain_bytes)
+    fn encrypt_take(&self, plain_bytes: &[u8], len: &usize)
+        -> Result<Vec<u8>, CryptoError> {
+
+        Ok(self.encrypt(plain_bytes)?
             .iter()
             .take(*len)
             .cloned()
-            .collect()
+            .collect())
     }
 
-    fn ecb_next_byte(&self, plain_bytes: &[u8]) -> Option<u8> {
+    fn ecb_next_byte(&self, plain_bytes: &[u8])
+        -> Result<Option<u8>, CryptoError> {
+
         let plain_bytes_len = plain_bytes.len();
 
         // Prepare a prepend vector such that the size of the input string
@@ -208,27 +235,623 @@ impl<'a> OracleWrapper<'a> {
 
         // Length of the cipher text under consideration
         let cipher_len = prepend_len + plain_bytes_len + 1;
-        let cipher_output = self.encrypt_take(&prepend_bytes, &cipher_len);
+        let cipher_output = self.encrypt_take(&prepend_bytes, &cipher_len)?;
 
         prepend_bytes.extend(plain_bytes);
 
-        (0..255).find(|byte| {
+        for byte in 0..255 {
             let mut input = prepend_bytes.clone();
-            input.push(*byte as u8);
+            input.push(byte);
 
-            self.encrypt_take(&input, &cipher_len)
+            if self.encrypt_take(&input, &cipher_len)?
                 .iter()
-                .eq(cipher_output.iter())
+                .eq(cipher_output.iter()) {
+
+                return Ok(Some(byte));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+// CBC padding oracle attack. The padding oracle is a function taking
+// an IV and a cipher text, which only tells whether the cipher text
+// decrypts to a valid PKCS#7 padding.
+pub struct PaddingOracleAnalyzer<F> where F: Fn(&[u8], &[u8]) -> bool {
+    padding_oracle: F,
+    keysize: usize,
+    queries: Cell<usize>,
+}
+
+impl<F> PaddingOracleAnalyzer<F> where F: Fn(&[u8], &[u8]) -> bool {
+    pub fn new(padding_oracle: F) -> Self {
+        PaddingOracleAnalyzer { padding_oracle, keysize: 16,
+            queries: Cell::new(0) }
+    }
+
+    // Number of times the padding oracle has been called so far
+    pub fn queries(&self) -> usize {
+        self.queries.get()
+    }
+
+    pub fn analyze(&self, iv: &[u8], cipher_bytes: &[u8])
+        -> Result<Vec<u8>, AnalyzerError> {
+
+        if iv.len() != self.keysize {
+            Err(CryptoError::InvalidIvLength(iv.len()))?;
+        }
+
+        if cipher_bytes.is_empty() ||
+            cipher_bytes.len() % self.keysize != 0 {
+
+            Err(CryptoError::NotBlockAligned(cipher_bytes.len()))?;
+        }
+
+        let mut plain_bytes = Vec::new();
+        let mut prev_block = iv;
+
+        // Each block is attacked on its own, with the previous cipher
+        // block (or the IV for the first one) as the IV we tamper with
+        for block in cipher_bytes.chunks(self.keysize) {
+            let intermediate = self.decrypt_block(prev_block, block)?;
+            plain_bytes.extend(xor(&intermediate, prev_block));
+            prev_block = block;
+        }
+
+        Ok(pkcs7::unpad(&plain_bytes).map_err(CryptoError::from)?)
+    }
+
+    fn is_padding_valid(&self, iv: &[u8], block: &[u8]) -> bool {
+        self.queries.set(self.queries.get() + 1);
+        (self.padding_oracle)(iv, block)
+    }
+
+    // Finds the block cipher decryption of the block, before it gets
+    // xor-ed with the previous block, one byte at a time from the end
+    fn decrypt_block(&self, prev_block: &[u8], block: &[u8])
+        -> Result<Vec<u8>, AnalyzerError> {
+
+        let mut intermediate = vec![0; self.keysize];
+        let mut forged_iv = prev_block.to_vec();
+
+        for pad in 1..(self.keysize + 1) {
+            let pos = self.keysize - pad;
+
+            // Make the bytes already found decrypt to the new padding
+            for i in (pos + 1)..self.keysize {
+                forged_iv[i] = intermediate[i] ^ pad as u8;
+            }
+
+            let byte = (0..256)
+                .map(|guess| guess as u8)
+                .find(|&guess| {
+                    forged_iv[pos] = guess;
+                    self.is_padding_valid(&forged_iv, block) &&
+                        (pad != 1 || !self.is_false_positive(&forged_iv,
+                            block, pos))
+                })
+                .ok_or(AnalyzerError::UnknownError)?;
+
+            intermediate[pos] = byte ^ pad as u8;
+        }
+
+        Ok(intermediate)
+    }
+
+    // A valid padding while looking for the last byte could also be
+    // \x02\x02 (or longer) if the byte before happens to decrypt to
+    // \x02. Changing that byte breaks such a padding, but not \x01.
+    fn is_false_positive(&self, forged_iv: &[u8], block: &[u8], pos: usize)
+        -> bool {
+
+        if pos == 0 {
+            return false;
+        }
+
+        let mut check_iv = forged_iv.to_vec();
+        check_iv[pos - 1] ^= 0xff;
+
+        !self.is_padding_valid(&check_iv, block)
+    }
+}
+
+// Breaks CTR cipher texts that were all encrypted with the same key and
+// nonce, and so the same keystream. Byte i of every cipher text is
+// xor-ed with the same keystream byte, so each column of bytes can be
+// solved like a single byte xor (Challenge 3).
+pub struct FixedNonceCtrAnalyzer {
+    cipher_texts: Vec<Vec<u8>>,
+    keystream: Vec<u8>,
+    confidence: Vec<f32>,
+}
+
+impl FixedNonceCtrAnalyzer {
+    // Columns run as far as the longest cipher text, so the end of the
+    // keystream rests on fewer samples than the start
+    pub fn new(cipher_texts: Vec<Vec<u8>>) -> Self {
+        let len = cipher_texts.iter()
+            .map(|cipher_bytes| cipher_bytes.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut analyzer = FixedNonceCtrAnalyzer { cipher_texts,
+            keystream: vec![0; len], confidence: vec![0.0; len] };
+
+        for index in 0..len {
+            analyzer.solve_column(index);
+        }
+
+        analyzer
+    }
+
+    // Cuts all cipher texts down to the shortest one, so that every
+    // column has a sample from every cipher text
+    pub fn truncated(cipher_texts: Vec<Vec<u8>>) -> Self {
+        let len = cipher_texts.iter()
+            .map(|cipher_bytes| cipher_bytes.len())
+            .min()
+            .unwrap_or(0);
+
+        FixedNonceCtrAnalyzer::new(cipher_texts.iter()
+            .map(|cipher_bytes| cipher_bytes[..len].to_vec())
+            .collect())
+    }
+
+    pub fn keystream(&self) -> &[u8] {
+        &self.keystream
+    }
+
+    // Per column, how far ahead the best keystream byte scored over the
+    // runner up, from 0 (a tie) to 1 (the only candidate)
+    pub fn confidence(&self) -> &[f32] {
+        &self.confidence
+    }
+
+    pub fn low_confidence_columns(&self, threshold: f32) -> Vec<usize> {
+        self.confidence.iter()
+            .enumerate()
+            .filter(|&(_, &confidence)| confidence < threshold)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn plain_texts(&self) -> Vec<Vec<u8>> {
+        self.cipher_texts.iter()
+            .map(|cipher_bytes| xor(cipher_bytes, &self.keystream))
+            .collect()
+    }
+
+    // Manual override, for the columns statistics gets wrong
+    pub fn set_keystream_byte(&mut self, index: usize, byte: u8) {
+        self.keystream[index] = byte;
+        self.confidence[index] = 1.0;
+    }
+
+    // Same as set_keystream_byte, but from a guess of what a plain text
+    // byte should be
+    pub fn set_plain_byte(&mut self, cipher_index: usize, index: usize,
+        plain_byte: u8) {
+
+        let cipher_byte = self.cipher_texts[cipher_index][index];
+        self.set_keystream_byte(index, cipher_byte ^ plain_byte);
+    }
+
+    fn column(&self, index: usize) -> Vec<u8> {
+        self.cipher_texts.iter()
+            .filter_map(|cipher_bytes| cipher_bytes.get(index))
+            .cloned()
+            .collect()
+    }
+
+    fn solve_column(&mut self, index: usize) {
+        let column = self.column(index);
+
+        let mut scores = (0..256)
+            .map(|byte| byte as u8)
+            .filter_map(|byte| {
+                let plain_bytes = column.iter()
+                    .map(|cipher_byte| cipher_byte ^ byte)
+                    .collect::<Vec<u8>>();
+
+                if plain_bytes.iter().all(|&byte| is_printable(byte)) {
+                    from_utf8(&plain_bytes).ok().map(|plain_text| {
+                        (english::frequency_score(plain_text), byte)
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<(f32, u8)>>();
+
+        scores.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+        let (keystream_byte, confidence) = match scores.len() {
+            0 => (0, 0.0),
+            1 => (scores[0].1, 1.0),
+            _ if scores[0].0 > 0.0 =>
+                (scores[0].1, (scores[0].0 - scores[1].0) / scores[0].0),
+            _ => (scores[0].1, 0.0)
+        };
+
+        self.keystream[index] = keystream_byte;
+        self.confidence[index] = confidence;
+    }
+}
+
+// Rebuilds an MT19937 from 624 consecutive outputs, so that it predicts
+// every output after them (Challenge 23). The outputs need not start at
+// the beginning of a state block.
+pub fn clone_mt19937(outputs: &[u32]) -> Result<Mt19937, AnalyzerError> {
+    if outputs.len() != mt19937::N {
+        Err(AnalyzerError::InvalidOutputCount(outputs.len()))?;
+    }
+
+    let offset = find_block_offset(outputs);
+
+    // Words from the offset onwards were output from one state block, the
+    // ones before it from the next block. Laid out in place, that is the
+    // state of a twist half way through.
+    let mut state = [0; mt19937::N];
+    for (index, &output) in outputs.iter().enumerate() {
+        state[(offset + index) % mt19937::N] = mt19937::untemper(output);
+    }
+
+    Ok(Mt19937::from_state(state, offset))
+}
+
+// Words below the offset are twisted from the ones above, so the word just
+// below it must match the twist of its neighbours. Only the top bit of the
+// word it replaced is unknown, which leaves 30 bits to check. No offset
+// matching means the outputs line up with a block.
+fn find_block_offset(outputs: &[u32]) -> usize {
+    let n = mt19937::N;
+    let words = outputs.iter()
+        .map(|&output| mt19937::untemper(output))
+        .collect::<Vec<u32>>();
+
+    // Word at state index i, if the stream starts at offset
+    let word = |offset: usize, i: usize| words[(i + n - offset) % n];
+
+    (1..n).find(|&offset| {
+        let i = offset - 1;
+        let next_word = word(offset, i + 1);
+        let ahead_word = word(offset, (i + mt19937::M) % n);
+
+        [0, 0x80000000].iter().any(|&top_bit| {
+            mt19937::twist_word(top_bit, next_word, ahead_word) ==
+                word(offset, i)
+        })
+    }).unwrap_or(0)
+}
+
+// Tries every second from now back to window seconds ago as the seed of
+// an MT19937 whose first output is the one given (Challenge 22)
+pub fn recover_time_seed(output: u32, now: u32, window: u32)
+    -> Result<u32, AnalyzerError> {
+
+    find_time_seed(now, window, |mt| mt.extract() == output)
+}
+
+// Same, for a token made of consecutive outputs, as handed out by
+// TimeSeededOracle::reset_token
+pub fn recover_token_seed(token: &[u8], now: u32, window: u32)
+    -> Result<u32, AnalyzerError> {
+
+    find_time_seed(now, window, |mt| mt.extract_bytes(token.len()) == token)
+}
+
+fn find_time_seed<F>(now: u32, window: u32, matches: F)
+    -> Result<u32, AnalyzerError> where F: Fn(&mut Mt19937) -> bool {
+
+    (now.saturating_sub(window)..=now)
+        .rev()
+        .find(|&seed| matches(&mut Mt19937::new(seed)))
+        .ok_or(AnalyzerError::SeedNotFound)
+}
+
+// Forges a cipher text that decrypts to have the target in it, from an
+// encrypt function that puts user data after a prefix (Challenge 16, 26).
+// Xor-ing a cipher text byte flips the same bits of the plain text at the
+// same position for CTR, and one block later for CBC.
+pub struct BitflipAnalyzer<F>
+    where F: Fn(&[u8]) -> Result<Vec<u8>, CryptoError> {
+
+    mode: CipherMode,
+    encrypt: F,
+}
+
+impl<F> BitflipAnalyzer<F>
+    where F: Fn(&[u8]) -> Result<Vec<u8>, CryptoError> {
+
+    pub fn new(mode: CipherMode, encrypt: F) -> Self {
+        BitflipAnalyzer { mode, encrypt }
+    }
+
+    // Length of what the encrypt function puts before user data
+    pub fn prefix_len(&self) -> Result<usize, AnalyzerError> {
+        let first_diff = self.first_diff(b"A", b"B")?;
+
+        match self.mode {
+            CipherMode::Ctr => Ok(first_diff),
+            CipherMode::Cbc => {
+                // Pad until the differing byte moves into the next
+                // block, the padding then fills up the prefix block
+                let block = first_diff / 16;
+
+                for pad_len in 0..17 {
+                    let mut left = vec![b'A'; pad_len];
+                    let mut right = left.clone();
+                    left.push(b'A');
+                    right.push(b'B');
+
+                    if self.first_diff(&left, &right)? / 16 > block {
+                        return Ok((block + 1) * 16 - pad_len);
+                    }
+                }
+
+                Err(AnalyzerError::UnknownError)
+            }
+        }
+    }
+
+    pub fn forge(&self, target: &[u8]) -> Result<Vec<u8>, AnalyzerError> {
+        let prefix_len = self.prefix_len()?;
+        self.forge_at(target, prefix_len)
+    }
+
+    pub fn forge_at(&self, target: &[u8], prefix_len: usize)
+        -> Result<Vec<u8>, AnalyzerError> {
+
+        // CBC garbles the block that is flipped, so the target gets a
+        // block of its own with a spare block before it to flip
+        let (user_bytes, flip_at) = match self.mode {
+            CipherMode::Ctr => (vec![b'A'; target.len()], prefix_len),
+            CipherMode::Cbc => {
+                if target.len() > 16 {
+                    Err(AnalyzerError::InvalidTargetLength(target.len()))?;
+                }
+
+                let align = (16 - prefix_len % 16) % 16;
+                (vec![b'A'; align + 16 + target.len()], prefix_len + align)
+            }
+        };
+
+        let mut cipher_bytes = (self.encrypt)(&user_bytes)?;
+
+        for (index, byte) in target.iter().enumerate() {
+            cipher_bytes[flip_at + index] ^= b'A' ^ byte;
+        }
+
+        Ok(cipher_bytes)
+    }
+
+    fn first_diff(&self, left: &[u8], right: &[u8])
+        -> Result<usize, AnalyzerError> {
+
+        let left = (self.encrypt)(left)?;
+        let right = (self.encrypt)(right)?;
+
+        left.iter()
+            .zip(right.iter())
+            .position(|(left, right)| left != right)
+            .ok_or(AnalyzerError::UnknownError)
+    }
+}
+
+// Recovers a CBC key that is also used as the IV, given a decrypt function
+// that returns the plain text of a message it rejects (Challenge 27).
+// Sending C1, 0, C1 decrypts the first block to P1 = D(C1) ^ key, and the
+// third to P3 = D(C1) ^ 0, so P1 ^ P3 is the key. The last two blocks of
+// the original go on the end to keep the padding valid.
+pub fn recover_key_as_iv<F>(cipher_bytes: &[u8], decrypt: F)
+    -> Result<Vec<u8>, AnalyzerError>
+    where F: Fn(&[u8]) -> Result<Vec<u8>, CryptoError> {
+
+    if cipher_bytes.len() % 16 != 0 {
+        Err(CryptoError::NotBlockAligned(cipher_bytes.len()))?;
+    }
+
+    if cipher_bytes.len() < 48 {
+        Err(AnalyzerError::InvalidCipherLength(cipher_bytes.len()))?;
+    }
+
+    let mut forged = cipher_bytes[..16].to_vec();
+    forged.extend(&[0; 16]);
+    forged.extend(&cipher_bytes[..16]);
+    forged.extend(&cipher_bytes[cipher_bytes.len() - 32..]);
+
+    match decrypt(&forged) {
+        Err(CryptoError::NonAscii(plain_bytes)) =>
+            Ok(xor(&plain_bytes[..16], &plain_bytes[32..48])),
+        Err(error) => Err(error.into()),
+        // Every byte of the third block came out below 0x80
+        Ok(_) => Err(AnalyzerError::UnknownError)
+    }
+}
+
+// Forges the SHA-1 secret-prefix MAC of message || glue padding || suffix
+// from the MAC of message alone (Challenge 29). The glue padding depends
+// on the key length, so each length up to max_key_len is tried against
+// the verifier. Returns the forged message and its MAC.
+pub fn forge_sha1_mac<F>(message: &[u8], mac: &[u8], suffix: &[u8],
+    max_key_len: usize, verify: F)
+    -> Result<(Vec<u8>, Vec<u8>), AnalyzerError>
+    where F: Fn(&[u8], &[u8]) -> bool {
+
+    let mut digest = [0; sha1::DIGEST_SIZE];
+    if mac.len() != digest.len() {
+        Err(AnalyzerError::InvalidMacLength(mac.len()))?;
+    }
+    digest.copy_from_slice(mac);
+
+    forge_mac(message, suffix, max_key_len, verify, sha1::md_padding,
+        |hashed_len| {
+            let mut sha1 = Sha1::from_digest(&digest, hashed_len);
+            sha1.update(suffix);
+            sha1.finalize().to_vec()
+        })
+}
+
+// Same as forge_sha1_mac, for MD4 (Challenge 30)
+pub fn forge_md4_mac<F>(message: &[u8], mac: &[u8], suffix: &[u8],
+    max_key_len: usize, verify: F)
+    -> Result<(Vec<u8>, Vec<u8>), AnalyzerError>
+    where F: Fn(&[u8], &[u8]) -> bool {
+
+    let mut digest = [0; md4::DIGEST_SIZE];
+    if mac.len() != digest.len() {
+        Err(AnalyzerError::InvalidMacLength(mac.len()))?;
+    }
+    digest.copy_from_slice(mac);
+
+    forge_mac(message, suffix, max_key_len, verify, md4::md_padding,
+        |hashed_len| {
+            let mut md4 = Md4::from_digest(&digest, hashed_len);
+            md4.update(suffix);
+            md4.finalize().to_vec()
         })
+}
+
+// Extend hashes suffix on from the MAC, given how many bytes the MAC
+// covered with padding
+fn forge_mac<F, E>(message: &[u8], suffix: &[u8], max_key_len: usize,
+    verify: F, md_padding: fn(u64) -> Vec<u8>, extend: E)
+    -> Result<(Vec<u8>, Vec<u8>), AnalyzerError>
+    where F: Fn(&[u8], &[u8]) -> bool, E: Fn(u64) -> Vec<u8> {
+
+    for key_len in 0..(max_key_len + 1) {
+        let glue = md_padding((key_len + message.len()) as u64);
+        let hashed_len = key_len + message.len() + glue.len();
+        let forged_mac = extend(hashed_len as u64);
+
+        let mut forged = message.to_vec();
+        forged.extend(glue);
+        forged.extend(suffix);
+
+        if verify(&forged, &forged_mac) {
+            return Ok((forged, forged_mac));
+        }
+    }
+
+    Err(AnalyzerError::KeyLengthNotFound)
+}
+
+// Recovers the first count bytes of a MAC of mac_len bytes from a verifier
+// that compares a byte at a time and takes longer the more bytes are right
+// (Challenge 31). Each byte is the guess that took longest to reject. Noise
+// only ever adds time, so the fastest of a few tries stands for a guess.
+// This holds up only while the leak dwarfs the noise.
+pub fn recover_mac_naive<F>(mac_len: usize, count: usize, verify: F)
+    -> Vec<u8> where F: Fn(&[u8]) -> bool {
+
+    recover_mac_naive_with(mac_len, count, |mac| {
+        let start = Instant::now();
+        let accepted = verify(mac);
+        (accepted, start.elapsed())
+    })
+}
+
+// Same attack on a verifier that reports how long it took, wall clock or
+// not
+fn recover_mac_naive_with<F>(mac_len: usize, count: usize, mut verify: F)
+    -> Vec<u8> where F: FnMut(&[u8]) -> (bool, Duration) {
+
+    let mut mac = vec![0; mac_len];
+
+    for index in 0..count.min(mac_len) {
+        let mut slowest = (0, 0);
+
+        for guess in 0..256 {
+            mac[index] = guess as u8;
+            let mut fastest = u128::MAX;
+
+            for _ in 0..3 {
+                let (accepted, elapsed) = verify(&mac);
+                if accepted {
+                    return mac;
+                }
+                fastest = fastest.min(elapsed.as_nanos());
+            }
+
+            if fastest > slowest.0 {
+                slowest = (fastest, guess as u8);
+            }
+        }
+
+        mac[index] = slowest.1;
+    }
+
+    mac.truncate(count);
+    mac
+}
+
+// Recovers the plain text behind a CTR cipher text, given random access
+// write to it (Challenge 25). Writing the cipher text over itself xors it
+// with the keystream a second time, so a single edit gives it all away.
+pub fn analyze_ctr_edit<F>(cipher_bytes: &[u8], edit: F)
+    -> Result<Vec<u8>, AnalyzerError>
+    where F: Fn(&[u8], usize, &[u8]) -> Result<Vec<u8>, CryptoError> {
+
+    Ok(edit(cipher_bytes, 0, cipher_bytes)?)
+}
+
+// Brute forces the 16 bit key of an MT19937 stream cipher, from a cipher
+// text whose plain text is known to end with known_suffix (Challenge 24)
+pub fn recover_mt19937_key(cipher_bytes: &[u8], known_suffix: &[u8])
+    -> Result<Vec<u8>, AnalyzerError> {
+
+    for seed in 0..0x10000u32 {
+        let key = vec![(seed >> 8) as u8, seed as u8];
+        let plain_bytes = Mt19937Cryptor.decrypt(cipher_bytes, &key)?;
+
+        if plain_bytes.ends_with(known_suffix) {
+            return Ok(key);
+        }
     }
+
+    Err(AnalyzerError::SeedNotFound)
+}
+
+// Whether the token came out of an MT19937 seeded with a time in the last
+// window seconds
+pub fn is_time_seeded_token(token: &[u8], now: u32, window: u32) -> bool {
+    recover_token_seed(token, now, window).is_ok()
 }
 
+fn is_printable(byte: u8) -> bool {
+    byte == b'\n' || (0x20..0x7f).contains(&byte)
+}
+
+// Payloads are only read through Debug, which dead code analysis skips
 #[derive(Debug)]
+#[allow(dead_code)]
 pub enum AnalyzerError {
     NotEcb,
+    CryptoError(CryptoError),
+    InvalidOutputCount(usize),
+    InvalidTargetLength(usize),
+    InvalidCipherLength(usize),
+    InvalidMacLength(usize),
+    KeyLengthNotFound,
+    TimingInconclusive,
+    Io(io::Error),
+    SeedNotFound,
     UnknownError
 }
 
+impl From<CryptoError> for AnalyzerError {
+    fn from(error: CryptoError) -> Self {
+        AnalyzerError::CryptoError(error)
+    }
+}
+
+impl From<io::Error> for AnalyzerError {
+    fn from(error: io::Error) -> Self {
+        AnalyzerError::Io(error)
+    }
+}
+
 pub fn is_ecb(bytes: &[u8], keysize: &usize) -> bool {
     let mut left = bytes.chunks(*keysize).collect::<Vec<&[u8]>>();
     left.sort();
@@ -248,6 +871,20 @@ pub fn is_ecb(bytes: &[u8], keysize: &usize) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use libs::oracle::PaddingOracle;
+    use libs::utils;
+    use libs::random::Random;
+    use libs::cryptor::Encryptor;
+    use libs::cryptor::Aes128CtrCryptor;
+    use libs::cryptor::CounterFormat;
+    use libs::clock::Clock;
+    use libs::clock::FakeClock;
+    use libs::oracle::TimeSeededOracle;
+    use libs::oracle::CookieOracle;
+    use libs::oracle::MacOracle;
+    use libs::oracle::HmacOracle;
+    use std::time::Duration;
+    use libs::cryptor::Aes128CbcDecryptor;
 
     #[test]
     fn test_is_ecb_positive() {
@@ -279,6 +916,448 @@ mod tests {
         assert_eq!(output, input);
     }
 
+    fn cbc_padding_oracle(oracle: &Oracle, input: &str) -> Vec<u8> {
+        let cipher_bytes = oracle.encrypt_cbc(input.as_bytes()).unwrap();
+
+        let analyzer = PaddingOracleAnalyzer::new(|iv, cipher_bytes| {
+            !matches!(oracle.decrypt_cbc_with_iv(iv, cipher_bytes),
+                Err(CryptoError::Padding(_)))
+        });
+
+        let plain_bytes = analyzer.analyze(oracle.iv(), &cipher_bytes)
+            .unwrap();
+
+        // At most 256 guesses per byte, plus one false positive check
+        // for the last byte of each block
+        let blocks = cipher_bytes.len() / 16;
+        assert!(analyzer.queries() > 0);
+        assert!(analyzer.queries() <= blocks * (16 * 256 + 1));
+
+        plain_bytes
+    }
+
+    #[test]
+    fn test_padding_oracle() {
+        let oracle = Oracle::new(None, None);
+
+        for input in ["foo", "PURPLE SPEEDBOAT",
+            "000001With the bass kicked in and the Vega's are pumpin'"].iter() {
+
+            let plain_bytes = cbc_padding_oracle(&oracle, input);
+            assert_eq!(plain_bytes.as_slice(), input.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_padding_oracle_against_padding_oracle() {
+        let secrets = vec!["foo".as_bytes().to_vec(),
+            "Cooking MC's like a pound of bacon".as_bytes().to_vec()];
+        let mut padding_oracle = PaddingOracle::new(secrets.clone());
+
+        for _ in 0..4 {
+            let (iv, cipher_bytes) = padding_oracle.encrypt().unwrap();
+            let analyzer = PaddingOracleAnalyzer::new(|iv, cipher_bytes| {
+                padding_oracle.is_padding_valid(iv, cipher_bytes)
+            });
+
+            let plain_bytes = analyzer.analyze(&iv, &cipher_bytes).unwrap();
+            assert!(secrets.contains(&plain_bytes));
+        }
+    }
+
+    #[test]
+    fn test_padding_oracle_false_positive() {
+        // Identity block cipher, so the intermediate bytes are just the
+        // cipher bytes and the guesses can be predicted
+        let analyzer = PaddingOracleAnalyzer::new(|iv, cipher_bytes| {
+            pkcs7::unpad(&xor(iv, cipher_bytes)).is_ok()
+        });
+
+        // Plain text ends in \x02\x01. As the cipher text ends in \x03,
+        // the guess making it \x02\x02 (1) comes before the right one (2)
+        let iv = "YELLOW SUBMARIN\x02".as_bytes();
+        let plain_bytes = "PURPLE SPEEDBO\x02\x01".as_bytes();
+        let cipher_bytes = xor(plain_bytes, iv);
+        assert_eq!(cipher_bytes[15], 3);
+
+        let output = analyzer.analyze(iv, &cipher_bytes).unwrap();
+        assert_eq!(output.as_slice(), "PURPLE SPEEDBO\x02".as_bytes());
+    }
+
+    #[test]
+    fn test_padding_oracle_not_block_aligned() {
+        let analyzer = PaddingOracleAnalyzer::new(|_, _| true);
+
+        match analyzer.analyze(&[0; 16], &[0; 20]) {
+            Err(AnalyzerError::CryptoError(
+                CryptoError::NotBlockAligned(20))) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    fn fixed_nonce_cipher_texts(plain_texts: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        let key = &mut [0; 16];
+        Random::new().fill_bytes(key);
+
+        let nonce = &[0; 8];
+        let cryptor = Aes128CtrCryptor::new(nonce,
+            CounterFormat::LittleEndian64);
+
+        plain_texts.iter()
+            .map(|plain_bytes| cryptor.encrypt(plain_bytes, key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_nonce_ctr() {
+        let plain_texts = utils::from_base64_lines("../resources/19.txt");
+        let cipher_texts = fixed_nonce_cipher_texts(&plain_texts);
+        let keystream = xor(&cipher_texts[0], &plain_texts[0]);
+
+        let mut analyzer = FixedNonceCtrAnalyzer::new(cipher_texts);
+        assert_eq!(analyzer.keystream().len(), 38);
+        assert_eq!(&analyzer.keystream()[1..20], &keystream[1..20]);
+
+        // Upper and lower case score the same, so first column is a tie
+        assert_eq!(analyzer.confidence()[0], 0.0);
+        assert!(analyzer.low_confidence_columns(0.1).contains(&0));
+
+        analyzer.set_plain_byte(0, 0, b'I');
+        assert_eq!(analyzer.keystream()[0], keystream[0]);
+        assert_eq!(analyzer.confidence()[0], 1.0);
+        assert_eq!(&analyzer.plain_texts()[3][..20],
+            &plain_texts[3][..20]);
+    }
+
+    #[test]
+    fn test_fixed_nonce_ctr_truncated() {
+        let plain_texts = utils::from_base64_lines("../resources/19.txt");
+        let cipher_texts = fixed_nonce_cipher_texts(&plain_texts);
+
+        let analyzer = FixedNonceCtrAnalyzer::truncated(cipher_texts);
+        let shortest = plain_texts.iter().map(|p| p.len()).min().unwrap();
+
+        assert_eq!(analyzer.keystream().len(), shortest);
+        assert!(analyzer.plain_texts()
+            .iter()
+            .all(|plain_bytes| plain_bytes.len() == shortest));
+    }
+
+    #[test]
+    fn test_fixed_nonce_ctr_set_keystream_byte() {
+        let mut analyzer = FixedNonceCtrAnalyzer::new(vec![vec![1, 2, 3]]);
+        analyzer.set_keystream_byte(1, 2);
+
+        assert_eq!(analyzer.keystream()[1], 2);
+        assert_eq!(analyzer.plain_texts()[0][1], 0);
+    }
+
+    fn assert_clones(seed: u32, skip: usize) {
+        let mut mt = Mt19937::new(seed);
+        for _ in 0..skip {
+            mt.extract();
+        }
+
+        let outputs = (0..mt19937::N).map(|_| mt.extract())
+            .collect::<Vec<u32>>();
+        let mut cloned = clone_mt19937(&outputs).unwrap();
+
+        for _ in 0..2000 {
+            assert_eq!(cloned.extract(), mt.extract());
+        }
+    }
+
+    #[test]
+    fn test_clone_mt19937() {
+        assert_clones(5489, 0);
+    }
+
+    #[test]
+    fn test_clone_mt19937_offset() {
+        for &skip in &[1, 2, 227, 396, 397, 398, 500, 623, 624, 1000] {
+            assert_clones(1234, skip);
+        }
+    }
+
+    #[test]
+    fn test_clone_mt19937_random_offsets() {
+        let mut random = Random::new();
+        for _ in 0..20 {
+            let seed = random.rand() as u32;
+            let skip = random.rand_range(&0, &2000) as usize;
+            assert_clones(seed, skip);
+        }
+    }
+
+    #[test]
+    fn test_clone_mt19937_invalid_output_count() {
+        match clone_mt19937(&[1, 2, 3]) {
+            Err(AnalyzerError::InvalidOutputCount(3)) => {},
+            _ => panic!("Expected InvalidOutputCount")
+        }
+    }
+
+    fn time_seeded_wait(clock: &FakeClock, random: &mut Random) {
+        clock.sleep(random.rand_range(&40, &1001) as u32);
+    }
+
+    #[test]
+    fn test_recover_time_seed() {
+        let clock = FakeClock::new(1500000000);
+        let mut random = Random::mt19937(22);
+        let oracle = TimeSeededOracle::new(&clock);
+
+        time_seeded_wait(&clock, &mut random);
+        let seed = clock.now();
+        let output = oracle.rand();
+        time_seeded_wait(&clock, &mut random);
+
+        assert_eq!(recover_time_seed(output, clock.now(), 2000).unwrap(),
+            seed);
+    }
+
+    #[test]
+    fn test_recover_time_seed_outside_window() {
+        let clock = FakeClock::new(1500000000);
+        let output = TimeSeededOracle::new(&clock).rand();
+        clock.sleep(100);
+
+        match recover_time_seed(output, clock.now(), 50) {
+            Err(AnalyzerError::SeedNotFound) => {},
+            _ => panic!("Expected SeedNotFound")
+        }
+    }
+
+    #[test]
+    fn test_recover_token_seed() {
+        let clock = FakeClock::new(1500000000);
+        let mut random = Random::mt19937(22);
+        let oracle = TimeSeededOracle::new(&clock);
+
+        time_seeded_wait(&clock, &mut random);
+        let seed = clock.now();
+        let token = oracle.reset_token(18);
+        time_seeded_wait(&clock, &mut random);
+
+        assert_eq!(recover_token_seed(&token, clock.now(), 2000).unwrap(),
+            seed);
+    }
+
+    fn assert_bitflip(mode: CipherMode) {
+        let cookie_oracle = CookieOracle::new(mode);
+        let analyzer = BitflipAnalyzer::new(mode, |bytes| {
+            cookie_oracle.encrypt_cookie(&String::from_utf8_lossy(bytes))
+        });
+
+        // Length of comment1=cooking%20MCs;userdata=
+        assert_eq!(analyzer.prefix_len().unwrap(), 32);
+
+        let cipher_bytes = analyzer.forge(b";admin=true;").unwrap();
+        assert!(cookie_oracle.is_admin(&cipher_bytes).unwrap());
+    }
+
+    #[test]
+    fn test_bitflip_cbc() {
+        assert_bitflip(CipherMode::Cbc);
+    }
+
+    #[test]
+    fn test_bitflip_ctr() {
+        assert_bitflip(CipherMode::Ctr);
+    }
+
+    #[test]
+    fn test_bitflip_prefix_len_unaligned() {
+        for &mode in &[CipherMode::Cbc, CipherMode::Ctr] {
+            for prefix_len in 0..40 {
+                let oracle = Oracle::new(Some(vec![b'x'; prefix_len]), None);
+                let analyzer = BitflipAnalyzer::new(mode, |bytes| {
+                    match mode {
+                        CipherMode::Cbc => oracle.encrypt_cbc(bytes),
+                        CipherMode::Ctr => oracle.encrypt_ctr(bytes)
+                    }
+                });
+
+                assert_eq!(analyzer.prefix_len().unwrap(), prefix_len);
+
+                let cipher_bytes = analyzer.forge(b"=admin;").unwrap();
+                let plain_bytes = match mode {
+                    CipherMode::Cbc => oracle.decrypt_cbc(&cipher_bytes),
+                    CipherMode::Ctr => oracle.decrypt_ctr(&cipher_bytes)
+                }.unwrap();
+                assert!(plain_bytes.ends_with(b"=admin;"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitflip_cbc_target_too_long() {
+        let oracle = Oracle::new(None, None);
+        let analyzer = BitflipAnalyzer::new(CipherMode::Cbc, |bytes| {
+            oracle.encrypt_cbc(bytes)
+        });
+
+        match analyzer.forge_at(&[b'a'; 17], 0) {
+            Err(AnalyzerError::InvalidTargetLength(17)) => {},
+            _ => panic!("Expected InvalidTargetLength")
+        }
+    }
+
+    #[test]
+    fn test_recover_key_as_iv() {
+        let oracle = Oracle::key_as_iv(None, None);
+        let plain_bytes = [b'A'; 48];
+        let cipher_bytes = oracle.encrypt_cbc(&plain_bytes).unwrap();
+
+        let key = recover_key_as_iv(&cipher_bytes, |cipher_bytes| {
+            oracle.decrypt_cbc_ascii(cipher_bytes)
+        }).unwrap();
+
+        let decryptor = Aes128CbcDecryptor(&key);
+        assert_eq!(decryptor.decrypt(&cipher_bytes, &key).unwrap(),
+            plain_bytes.to_vec());
+    }
+
+    #[test]
+    fn test_recover_key_as_iv_short() {
+        let oracle = Oracle::key_as_iv(None, None);
+        let cipher_bytes = oracle.encrypt_cbc(b"short").unwrap();
+
+        match recover_key_as_iv(&cipher_bytes, |cipher_bytes| {
+            oracle.decrypt_cbc_ascii(cipher_bytes)
+        }) {
+            Err(AnalyzerError::InvalidCipherLength(16)) => {},
+            _ => panic!("Expected InvalidCipherLength")
+        }
+    }
+
+    #[test]
+    fn test_forge_sha1_mac() {
+        let mac_oracle = MacOracle::sha1();
+        let message = b"comment1=cooking%20MCs;userdata=foo;\
+            comment2=%20like%20a%20pound%20of%20bacon";
+        let mac = mac_oracle.mac(message);
+
+        let (forged, forged_mac) = forge_sha1_mac(message, &mac,
+            b";admin=true", 32, |message, mac| mac_oracle.verify(message, mac))
+            .unwrap();
+
+        assert!(forged.starts_with(message));
+        assert!(forged.ends_with(b";admin=true"));
+        assert!(mac_oracle.verify(&forged, &forged_mac));
+    }
+
+    #[test]
+    fn test_forge_md4_mac() {
+        let mac_oracle = MacOracle::md4();
+        let message = b"comment1=cooking%20MCs;userdata=foo;\
+            comment2=%20like%20a%20pound%20of%20bacon";
+        let mac = mac_oracle.mac(message);
+
+        let (forged, forged_mac) = forge_md4_mac(message, &mac,
+            b";admin=true", 32, |message, mac| mac_oracle.verify(message, mac))
+            .unwrap();
+
+        assert!(forged.starts_with(message));
+        assert!(forged.ends_with(b";admin=true"));
+        assert!(mac_oracle.verify(&forged, &forged_mac));
+    }
+
+    #[test]
+    fn test_forge_md4_mac_invalid_mac_length() {
+        match forge_md4_mac(b"message", &[0; 20], b";admin=true", 32,
+            |_, _| true) {
+
+            Err(AnalyzerError::InvalidMacLength(20)) => {},
+            _ => panic!("Expected InvalidMacLength")
+        }
+    }
+
+    #[test]
+    fn test_forge_sha1_mac_key_too_long() {
+        let mac_oracle = MacOracle::sha1();
+        let mac = mac_oracle.mac(b"message");
+
+        // Keys are at least one byte long
+        match forge_sha1_mac(b"message", &mac, b";admin=true", 0,
+            |message, mac| mac_oracle.verify(message, mac)) {
+
+            Err(AnalyzerError::KeyLengthNotFound) => {},
+            _ => panic!("Expected KeyLengthNotFound")
+        }
+    }
+
+    #[test]
+    fn test_recover_mac_naive() {
+        let hmac_oracle = HmacOracle::new(Duration::from_millis(0));
+        let expected = hmac_oracle.hmac(b"foo");
+
+        // 5ms a matching byte, on a simulated clock
+        let mac = recover_mac_naive_with(20, 2, |signature| {
+            let matching = expected.iter()
+                .zip(signature.iter())
+                .take_while(|&(left, right)| left == right)
+                .count();
+
+            (hmac_oracle.verify(b"foo", signature),
+                Duration::from_millis(5 * matching as u64))
+        });
+        assert_eq!(mac, &expected[..2]);
+    }
+
+    #[test]
+    fn test_analyze_ctr_edit() {
+        let plain_bytes = utils::from_base64_lines("../resources/19.txt")
+            .concat();
+        let oracle = Oracle::new(None, None);
+        let cipher_bytes = oracle.encrypt_ctr(&plain_bytes).unwrap();
+
+        let calls = Cell::new(0);
+        let output = analyze_ctr_edit(&cipher_bytes, |cipher_bytes, offset,
+            new_bytes| {
+
+            calls.set(calls.get() + 1);
+            oracle.edit(cipher_bytes, offset, new_bytes)
+        }).unwrap();
+
+        assert_eq!(output, plain_bytes);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_recover_mt19937_key() {
+        let oracle = Oracle::random_prefix(None);
+        let known = [b'A'; 14];
+
+        let cipher_bytes = oracle.encrypt_mt19937(&known).unwrap();
+        let key = recover_mt19937_key(&cipher_bytes, &known).unwrap();
+
+        // Key has to work on a cipher text it was not recovered from
+        let other = b"YELLOW SUBMARINE";
+        let cipher_bytes = oracle.encrypt_mt19937(other).unwrap();
+        let plain_bytes = Mt19937Cryptor.decrypt(&cipher_bytes, &key)
+            .unwrap();
+        assert!(plain_bytes.ends_with(other));
+    }
+
+    #[test]
+    fn test_is_time_seeded_token() {
+        let clock = FakeClock::new(1500000000);
+        let token = TimeSeededOracle::new(&clock).reset_token(16);
+        clock.sleep(300);
+
+        assert!(is_time_seeded_token(&token, clock.now(), 1000));
+    }
+
+    #[test]
+    fn test_is_time_seeded_token_negative() {
+        let mut random = Random::new();
+        let token = &mut [0; 16];
+        random.fill_bytes(token);
+
+        assert!(!is_time_seeded_token(token, 1500000000, 1000));
+    }
+
     #[test]
     fn test_analyze_ecb_less_than_keysize() {
         let input = "PURPLE SPEEDBOA";
@@ -0,0 +1,180 @@
+// gcd, mod_inv and the roots are there for RSA, which no challenge here
+// gets to yet
+#![allow(dead_code)]
+
+use num_bigint::{BigInt, Sign};
+use rustc_serialize::hex::{FromHex, ToHex};
+
+pub use num_bigint::BigUint;
+
+// Big endian, the way numbers go on the wire
+pub fn from_bytes(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+// Shortest big endian form, a single zero byte for zero
+pub fn to_bytes(num: &BigUint) -> Vec<u8> {
+    num.to_bytes_be()
+}
+
+pub fn from_hex(hex: &str) -> Option<BigUint> {
+    let hex = if hex.len() % 2 == 1 {
+        format!("0{}", hex)
+    } else {
+        hex.to_string()
+    };
+
+    hex.from_hex().ok().map(|bytes| from_bytes(&bytes))
+}
+
+pub fn to_hex(num: &BigUint) -> String {
+    to_bytes(num).to_hex()
+}
+
+pub fn mod_pow(base: &BigUint, exponent: &BigUint, modulus: &BigUint)
+    -> BigUint {
+
+    base.modpow(exponent, modulus)
+}
+
+pub fn gcd(left: &BigUint, right: &BigUint) -> BigUint {
+    let mut a = left.clone();
+    let mut b = right.clone();
+
+    while b != BigUint::from(0u32) {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+
+    a
+}
+
+// Extended Euclid, carrying only the coefficient of num. None unless num
+// and modulus are coprime.
+pub fn mod_inv(num: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let zero = BigInt::from(0);
+    let modulus = BigInt::from_biguint(Sign::Plus, modulus.clone());
+
+    let (mut r0, mut r1) = (modulus.clone(),
+        BigInt::from_biguint(Sign::Plus, num.clone()) % &modulus);
+    let (mut t0, mut t1) = (zero.clone(), BigInt::from(1));
+
+    while r1 != zero {
+        let q = &r0 / &r1;
+
+        let r2 = &r0 - &q * &r1;
+        r0 = r1;
+        r1 = r2;
+
+        let t2 = &t0 - &q * &t1;
+        t0 = t1;
+        t1 = t2;
+    }
+
+    if r0 != BigInt::from(1) {
+        return None;
+    }
+
+    if t0 < zero {
+        t0 += &modulus;
+    }
+
+    t0.to_biguint()
+}
+
+// Largest root such that root^n <= num
+pub fn nth_root(num: &BigUint, n: u32) -> BigUint {
+    assert!(n > 0);
+    num.nth_root(n)
+}
+
+pub fn cube_root(num: &BigUint) -> BigUint {
+    nth_root(num, 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(num: u64) -> BigUint {
+        BigUint::from(num)
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let num = from_bytes(&[0x01, 0x00, 0xff]);
+
+        assert_eq!(num, big(0x0100ff));
+        assert_eq!(to_bytes(&num), vec![0x01, 0x00, 0xff]);
+        assert_eq!(to_bytes(&big(0)), vec![0]);
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(from_hex("deadbeef"), Some(big(0xdeadbeef)));
+        assert_eq!(from_hex("abc"), Some(big(0xabc)));
+        assert_eq!(from_hex("xyz"), None);
+        assert_eq!(to_hex(&big(0xabc)), "0abc");
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(mod_pow(&big(4), &big(13), &big(497)), big(445));
+        assert_eq!(mod_pow(&big(5), &big(0), &big(7)), big(1));
+    }
+
+    #[test]
+    fn test_mod_pow_fermat() {
+        // 2^127 - 1 is prime, so a^(p-1) = 1 mod p
+        let p = from_hex("7fffffffffffffffffffffffffffffff").unwrap();
+        let exponent = &p - big(1);
+
+        assert_eq!(mod_pow(&big(3), &exponent, &p), big(1));
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(&big(48), &big(18)), big(6));
+        assert_eq!(gcd(&big(17), &big(5)), big(1));
+        assert_eq!(gcd(&big(0), &big(5)), big(5));
+    }
+
+    #[test]
+    fn test_mod_inv() {
+        // From the RSA example on Wikipedia
+        assert_eq!(mod_inv(&big(17), &big(3120)), Some(big(2753)));
+        assert_eq!(mod_inv(&big(3), &big(11)), Some(big(4)));
+    }
+
+    #[test]
+    fn test_mod_inv_not_coprime() {
+        assert_eq!(mod_inv(&big(6), &big(9)), None);
+    }
+
+    #[test]
+    fn test_mod_inv_large() {
+        let p = from_hex("7fffffffffffffffffffffffffffffff").unwrap();
+        let num = from_hex("123456789abcdef0123456789").unwrap();
+        let inverse = mod_inv(&num, &p).unwrap();
+
+        assert_eq!((num * inverse) % p, big(1));
+    }
+
+    #[test]
+    fn test_cube_root() {
+        let root = from_hex("123456789abcdef0123456789").unwrap();
+        let cube = &root * &root * &root;
+
+        assert_eq!(cube_root(&cube), root);
+        assert_eq!(cube_root(&(&cube - big(1))), &root - big(1));
+        assert_eq!(cube_root(&(&cube + big(1))), root);
+    }
+
+    #[test]
+    fn test_nth_root() {
+        assert_eq!(nth_root(&big(1 << 40), 5), big(1 << 8));
+        assert_eq!(nth_root(&big(99), 2), big(9));
+        assert_eq!(nth_root(&big(12345), 1), big(12345));
+    }
+}
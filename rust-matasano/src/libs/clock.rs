@@ -0,0 +1,68 @@
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(test)]
+use std::cell::Cell;
+
+// Unix time in seconds. Challenges that wait on the clock take one of
+// these, so tests can fast forward instead of sleeping.
+pub trait Clock {
+    fn now(&self) -> u32;
+    fn sleep(&self, seconds: u32);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u32 {
+        SystemTime::now().duration_since(UNIX_EPOCH)
+            .expect("Clock is before 1970")
+            .as_secs() as u32
+    }
+
+    fn sleep(&self, seconds: u32) {
+        thread::sleep(Duration::from_secs(seconds as u64));
+    }
+}
+
+// Only the tests need a clock that can be fast forwarded
+#[cfg(test)]
+pub struct FakeClock {
+    time: Cell<u32>,
+}
+
+#[cfg(test)]
+impl FakeClock {
+    pub fn new(time: u32) -> Self {
+        FakeClock { time: Cell::new(time) }
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> u32 {
+        self.time.get()
+    }
+
+    fn sleep(&self, seconds: u32) {
+        self.time.set(self.time.get() + seconds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_clock_sleep() {
+        let clock = FakeClock::new(1000);
+        clock.sleep(40);
+
+        assert_eq!(clock.now(), 1040);
+    }
+
+    #[test]
+    fn test_system_clock_now() {
+        // Some time after this was written
+        assert!(SystemClock.now() > 1500000000);
+    }
+}
@@ -1,161 +1,234 @@
-use crypto::buffer::{ ReadBuffer, WriteBuffer, RefReadBuffer, RefWriteBuffer,
-    BufferResult};
-use crypto::buffer::BufferResult::{ BufferUnderflow, BufferOverflow};
-use crypto::aes::{ ecb_decryptor, ecb_encryptor, KeySize};
-use crypto::blockmodes::{ PkcsPadding, NoPadding, PaddingProcessor};
-use crypto::symmetriccipher;
-use crypto::symmetriccipher::SymmetricCipherError;
-
-use libs::pkcs7::Pkcs7Pad;
-
-trait CryptHandler {
-    fn crypt(&mut self, read_buffer: &mut RefReadBuffer,
-         write_buffer: &mut RefWriteBuffer)
-        -> Result<BufferResult, SymmetricCipherError>;
+// Not every counter layout here has a challenge that uses it
+#![allow(dead_code)]
+
+use libs::aes::Aes;
+use libs::modes::{ BlockMode, Ecb, Cbc, Padding, to_block, to_iv};
+use libs::pkcs7::PaddingError;
+use libs::mt19937::Mt19937;
+
+#[derive(Debug)]
+pub enum CryptoError {
+    InvalidKeyLength(usize),
+    InvalidIvLength(usize),
+    NotBlockAligned(usize),
+    InvalidOffset(usize),
+    NonAscii(Vec<u8>),
+    Padding(PaddingError),
 }
 
-impl CryptHandler for Box<dyn symmetriccipher::Encryptor> {
-    fn crypt(&mut self, read_buffer: &mut RefReadBuffer,
-         write_buffer: &mut RefWriteBuffer)
-        -> Result<BufferResult, SymmetricCipherError> {
-
-        self.encrypt(read_buffer, write_buffer, true)
-    }
-}
-
-impl CryptHandler for Box<dyn symmetriccipher::Decryptor> {
-    fn crypt(&mut self, read_buffer: &mut RefReadBuffer,
-         write_buffer: &mut RefWriteBuffer)
-        -> Result<BufferResult, SymmetricCipherError> {
-
-        self.decrypt(read_buffer, write_buffer, true)
+impl From<PaddingError> for CryptoError {
+    fn from(error: PaddingError) -> Self {
+        CryptoError::Padding(error)
     }
 }
 
-fn crypt<C: CryptHandler>(bytes: &[u8], mut crypt_handler: C)
-    -> Result<Vec<u8>, SymmetricCipherError> {
-
-    let mut final_result = Vec::<u8>::new();
-    let mut buffer = [0; 4096];
-    let read_buffer = &mut RefReadBuffer::new(bytes);
-    let write_buffer = &mut RefWriteBuffer::new(&mut buffer);
-
-    loop {
-        let result = crypt_handler.crypt(read_buffer, write_buffer)?;
-        final_result.extend(write_buffer
-            .take_read_buffer()
-            .take_remaining()
-            .iter()
-            .map(|&i| i));
-
-        match result {
-            BufferUnderflow => break,
-            BufferOverflow => { }
-        }
-    }
-
-    Ok(final_result)
-}
-
-fn xor(bytes: &[u8], key: &[u8]) -> Vec<u8> {
+pub fn xor(bytes: &[u8], key: &[u8]) -> Vec<u8> {
     bytes.iter()
         .zip(key)
         .map(|(byte1, byte2)| byte1 ^ byte2)
         .collect::<Vec<u8>>()
 }
 
-fn decrypt<P>(cipher_bytes: &[u8], key: &[u8], padding: P)
-    -> Result<Vec<u8>, SymmetricCipherError> 
-    where P: PaddingProcessor + Send + 'static {
-
-    let decryptor = ecb_decryptor(KeySize::KeySize128, key, padding);
-    crypt(cipher_bytes, decryptor)
-}
-
-fn encrypt<P>(plain_bytes: &[u8], key: &[u8], padding: P)
-    -> Result<Vec<u8>, SymmetricCipherError>
-    where P: PaddingProcessor + Send + 'static {
-
-    let encryptor = ecb_encryptor(KeySize::KeySize128, key, padding);
-    crypt(plain_bytes, encryptor)
+// A cipher that works on fixed size blocks of N bytes, like AES with
+// N = 16. Block modes in libs::modes are built on top of it.
+pub trait BlockCipher<const N: usize> {
+    fn encrypt_block(&self, block: &[u8; N]) -> [u8; N];
+    fn decrypt_block(&self, block: &[u8; N]) -> [u8; N];
 }
 
 pub trait Decryptor {
-    fn decrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8>;
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError>;
 }
 
 pub trait Encryptor {
-    fn encrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8>;
+    fn encrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError>;
 }
 
 pub struct XorDecryptor;
+
+// ECB decryption, AES-128, 192 or 256 depending on the key length
 pub struct Aes128EcbDecryptor;
+// ECB encryption, a 16, 24 or 32 byte key selects the AES variant
 pub struct Aes128EcbEncryptor;
+// CBC encryption under the given IV, with AES-128/192/256 chosen by
+// how long the key is
 pub struct Aes128CbcEncryptor<'a>(pub &'a [u8]);
+// CBC decryption under the given IV. Keys of 16, 24 and 32 bytes give
+// AES-128, 192 and 256
 pub struct Aes128CbcDecryptor<'a>(pub &'a [u8]);
 
+#[derive(Clone, Copy, Debug)]
+pub enum CounterFormat {
+    // 64 bit nonce followed by a 64 bit little endian block counter,
+    // as used in Cryptopals
+    LittleEndian64,
+    // 96 bit nonce followed by a 32 bit big endian block counter,
+    // as used in NIST SP 800-38A
+    BigEndian32,
+}
+
+impl CounterFormat {
+    fn nonce_len(&self) -> usize {
+        match *self {
+            CounterFormat::LittleEndian64 => 8,
+            CounterFormat::BigEndian32 => 12,
+        }
+    }
+}
+
+// CTR mode turns AES into a stream cipher, so the same struct does both
+// encryption and decryption and no padding is applied. Any AES key size
+// works, the key length decides between AES-128, 192 and 256.
+pub struct Aes128CtrCryptor<'a> {
+    nonce: &'a [u8],
+    format: CounterFormat,
+    counter: u64,
+}
+
+impl<'a> Aes128CtrCryptor<'a> {
+    pub fn new(nonce: &'a [u8], format: CounterFormat) -> Self {
+        Aes128CtrCryptor::with_counter(nonce, format, 0)
+    }
+
+    pub fn with_counter(nonce: &'a [u8], format: CounterFormat, counter: u64)
+        -> Self {
+
+        Aes128CtrCryptor { nonce, format, counter }
+    }
+
+    fn counter_block(&self, block_index: u64) -> [u8; 16] {
+        let mut block = self.nonce.to_vec();
+
+        match self.format {
+            CounterFormat::LittleEndian64 => {
+                let counter = self.counter.wrapping_add(block_index);
+                block.extend_from_slice(&counter.to_le_bytes());
+            },
+            CounterFormat::BigEndian32 => {
+                // Only the low 32 bits take part, the counter wraps
+                // around without touching the nonce
+                let counter = (self.counter as u32)
+                    .wrapping_add(block_index as u32);
+                block.extend_from_slice(&counter.to_be_bytes());
+            }
+        }
+
+        to_block(&block)
+    }
+
+    fn crypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        if self.nonce.len() != self.format.nonce_len() {
+            return Err(CryptoError::InvalidIvLength(self.nonce.len()));
+        }
+
+        let aes = Aes::new(key)?;
+        let mut output = Vec::with_capacity(bytes.len());
+
+        for (block_index, chunk) in bytes.chunks(16).enumerate() {
+            let counter_block = self.counter_block(block_index as u64);
+            let keystream = aes.encrypt_block(&counter_block);
+
+            // Last chunk may be shorter than a block, xor only
+            // takes as much keystream as needed
+            output.extend(xor(chunk, &keystream));
+        }
+
+        Ok(output)
+    }
+}
+
+// Stream cipher with an MT19937 keystream, seeded with a 16 bit key given
+// as two big endian bytes (Challenge 24)
+pub struct Mt19937Cryptor;
+
+impl Mt19937Cryptor {
+    fn crypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        if key.len() != 2 {
+            return Err(CryptoError::InvalidKeyLength(key.len()));
+        }
+
+        let seed = ((key[0] as u32) << 8) | key[1] as u32;
+        let keystream = Mt19937::new(seed).extract_bytes(bytes.len());
+
+        Ok(xor(bytes, &keystream))
+    }
+}
+
 impl Decryptor for XorDecryptor {
-    fn decrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8> {
-        xor(bytes, key)
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        Ok(xor(bytes, key))
     }
 }
 
 impl Decryptor for Aes128EcbDecryptor {
-    fn decrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8> {
-        decrypt(bytes, key, PkcsPadding).unwrap()
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        Ecb::new(Padding::Pkcs7).decrypt(&Aes::new(key)?, bytes)
     }
 }
 
 impl Encryptor for Aes128EcbEncryptor {
-    fn encrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8> {
-        encrypt(bytes, key, PkcsPadding).unwrap()
+    fn encrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        Ecb::new(Padding::Pkcs7).encrypt(&Aes::new(key)?, bytes)
     }
 }
 
 impl<'a> Encryptor for Aes128CbcEncryptor<'a> {
-    fn encrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8> {
-        let iv = Vec::from(self.0);
+    fn encrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
 
-        bytes.to_vec()
-            .pkcs7_pad(16)
-            .chunks(16)
-            .scan(iv, |prev, chunk| {
-                let xor_bytes = xor(prev, chunk);
-                let cipher_chunk = encrypt(xor_bytes.as_slice(), key, NoPadding)
-                    .unwrap();
+        Cbc::new(to_iv(self.0)?, Padding::Pkcs7).encrypt(&Aes::new(key)?, bytes)
+    }
+}
+
+impl<'a> Decryptor for Aes128CbcDecryptor<'a> {
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
 
-                prev.clear();
-                prev.extend(cipher_chunk.iter().cloned());
+        Cbc::new(to_iv(self.0)?, Padding::Pkcs7).decrypt(&Aes::new(key)?, bytes)
+    }
+}
 
-                Some(cipher_chunk)
-            }).flat_map(|cipher_chunk| cipher_chunk)
-            .collect()
+impl<'a> Encryptor for Aes128CtrCryptor<'a> {
+    fn encrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
 
+        self.crypt(bytes, key)
     }
 }
 
-impl<'a> Decryptor for Aes128CbcDecryptor<'a> {
-    fn decrypt(&self, bytes: &[u8], key: &[u8]) -> Vec<u8> {
-        let iv = Vec::from(self.0);
+impl<'a> Decryptor for Aes128CtrCryptor<'a> {
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
 
-        // Dereference operator needed here to unbox the unpad
-        // operation
-        *bytes.to_vec()
-            .chunks(16)
-            .scan(iv, |prev, chunk| {
-                let cipher_chunk = decrypt(chunk, key, NoPadding).unwrap();
-                let plain_chunk = xor(prev, &cipher_chunk);
+        self.crypt(bytes, key)
+    }
+}
 
-                prev.clear();
-                prev.extend(chunk.iter().cloned());
+impl Encryptor for Mt19937Cryptor {
+    fn encrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
 
-                Some(plain_chunk)
-            })
-            .flat_map(|cipher_chunk| cipher_chunk)
-            .collect::<Vec<u8>>()
-            .pkcs7_unpad()
-            .expect("Padding Error")
+        self.crypt(bytes, key)
+    }
+}
+
+impl Decryptor for Mt19937Cryptor {
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
 
+        self.crypt(bytes, key)
     }
 }
 
@@ -163,13 +236,15 @@ impl<'a> Decryptor for Aes128CbcDecryptor<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rustc_serialize::hex::FromHex;
 
     #[test]
     fn test_aes_ecb_encrypt() {
         let plain_bytes = "foo".as_bytes();
         let key = "YELLOW SUBMARINE".as_bytes();
 
-        let cipher_bytes = Aes128EcbEncryptor.encrypt(plain_bytes, key);
+        let cipher_bytes = Aes128EcbEncryptor.encrypt(plain_bytes, key)
+            .unwrap();
 
         // https://goo.gl/QMFvYv 
         let expected = &[94, 162, 90, 181, 151, 215, 195, 200, 101, 224, 126,
@@ -183,7 +258,8 @@ mod tests {
             126, 168, 205, 179, 168, 166];
         let key = "YELLOW SUBMARINE".as_bytes();
 
-        let plain_bytes = Aes128EcbDecryptor.decrypt(cipher_bytes, key);
+        let plain_bytes = Aes128EcbDecryptor.decrypt(cipher_bytes, key)
+            .unwrap();
         let expected = "foo".as_bytes();
         assert_eq!(plain_bytes.as_slice(), expected);
     }
@@ -194,7 +270,8 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv = "GREEN SPACECRAFT".as_bytes();
 
-        let cipher_bytes = Aes128CbcEncryptor(iv).encrypt(plain_bytes, key);
+        let cipher_bytes = Aes128CbcEncryptor(iv).encrypt(plain_bytes, key)
+            .unwrap();
 
         // https://goo.gl/sB3U12
         let expected = &[66, 19, 152, 42, 202, 25, 162, 144, 39, 160, 93, 255,
@@ -209,7 +286,8 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv = "GREEN SPACECRAFT".as_bytes();
 
-        let cipher_bytes = Aes128CbcEncryptor(iv).encrypt(plain_bytes, key);
+        let cipher_bytes = Aes128CbcEncryptor(iv).encrypt(plain_bytes, key)
+            .unwrap();
 
         // https://goo.gl/WUt5br
         let expected = &[118, 250, 12, 228, 185, 89, 251, 169, 77, 66, 236,
@@ -223,7 +301,8 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv = "GREEN SPACECRAFT".as_bytes();
 
-        let cipher_bytes = Aes128CbcEncryptor(iv).encrypt(plain_bytes, key);
+        let cipher_bytes = Aes128CbcEncryptor(iv).encrypt(plain_bytes, key)
+            .unwrap();
 
         // https://goo.gl/XbPq6B
         let expected = &[66, 19, 152, 42, 202, 25, 162, 144, 39, 160, 93, 255,
@@ -241,7 +320,8 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv = "GREEN SPACECRAFT".as_bytes();
 
-        let plain_bytes = Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key);
+        let plain_bytes = Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key)
+            .unwrap();
 
         let expected = "PURPLE SPEEDBOAT".as_bytes();
         assert_eq!(plain_bytes.as_slice(), expected);
@@ -254,7 +334,8 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv = "GREEN SPACECRAFT".as_bytes();
 
-        let plain_bytes = Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key);
+        let plain_bytes = Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key)
+            .unwrap();
 
         let expected = "foo".as_bytes();
         assert_eq!(plain_bytes.as_slice(), expected);
@@ -268,10 +349,304 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv = "GREEN SPACECRAFT".as_bytes();
 
-        let plain_bytes = Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key);
+        let plain_bytes = Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key)
+            .unwrap();
 
         let expected = "PURPLE SPEEDBOAT foo".as_bytes();
         assert_eq!(plain_bytes.as_slice(), expected);
     }
 
+    // NIST SP 800-38A, F.5.1 CTR-AES128.Encrypt
+    const SP800_38A_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const SP800_38A_NONCE: &str = "f0f1f2f3f4f5f6f7f8f9fafb";
+    const SP800_38A_COUNTER: u64 = 0xfcfdfeff;
+    const SP800_38A_PLAIN: &str = "6bc1bee22e409f96e93d7e117393172a\
+        ae2d8a571e03ac9c9eb76fac45af8e51\
+        30c81c46a35ce411e5fbc1191a0a52ef\
+        f69f2445df4f9b17ad2b417be66c3710";
+    const SP800_38A_CIPHER: &str = "874d6191b620e3261bef6864990db6ce\
+        9806f66b7970fdff8617187bb9fffdff\
+        5ae4df3edbd5d35e5b4f09020db03eab\
+        1e031dda2fbe03d1792170a0f3009cee";
+
+    // FIPS-197, Appendix C
+    const FIPS_197_PLAIN: &str = "00112233445566778899aabbccddeeff";
+    const FIPS_197_VECTORS: &[(&str, &str)] = &[
+        ("000102030405060708090a0b0c0d0e0f",
+            "69c4e0d86a7b0430d8cdb78070b4c55a"),
+        ("000102030405060708090a0b0c0d0e0f1011121314151617",
+            "dda97ca4864cdfe06eaf70a0ec0d7191"),
+        ("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "8ea2b7ca516745bfeafc49904b496089"),
+    ];
+
+    // NIST SP 800-38A, F.1 (ECB), F.2 (CBC) and F.5 (CTR) for
+    // AES-128, AES-192 and AES-256. Plain text is SP800_38A_PLAIN.
+    const SP800_38A_KEYS: &[&str] = &[
+        "2b7e151628aed2a6abf7158809cf4f3c",
+        "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+        "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+    ];
+    const SP800_38A_IV: &str = "000102030405060708090a0b0c0d0e0f";
+    const SP800_38A_ECB_CIPHERS: &[&str] = &[
+        "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf\
+            43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4",
+        "bd334f1d6e45f25ff712a214571fa5cc974104846d0ad3ad7734ecb3ecee4eef\
+            ef7afd2270e2e60adce0ba2face6444e9a4b41ba738d6c72fb16691603c18e0e",
+        "f3eed1bdb5d2a03c064b5a7e3db181f8591ccb10d410ed26dc5ba74a31362870\
+            b6ed21b99ca6f4f9f153e7b1beafed1d23304b7a39f9f3ff067d8d8f9e24ecc7",
+    ];
+    const SP800_38A_CBC_CIPHERS: &[&str] = &[
+        "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2\
+            73bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7",
+        "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a\
+            571b242012fb7ae07fa9baac3df102e008b0e27988598881d920a9e64f5615cd",
+        "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d\
+            39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b",
+    ];
+    const SP800_38A_CTR_CIPHERS: &[&str] = &[
+        "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+            5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee",
+        "1abc932417521ca24f2b0459fe7e6e0b090339ec0aa6faefd5ccc2c6f4ce8e94\
+            1e36b26bd1ebc670d1bd1d665620abf74f78a7f6d29809585a97daec58c6b050",
+        "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5\
+            2b0930daa23de94ce87017ba2d84988ddfc9c58db67aada613c2dd08457941a6",
+    ];
+
+    #[test]
+    fn test_aes_fips_197() {
+        let plain_bytes = FIPS_197_PLAIN.from_hex().unwrap();
+
+        for &(key, cipher) in FIPS_197_VECTORS {
+            let aes = Aes::new(&key.from_hex().unwrap()).unwrap();
+            let ecb = Ecb::new(Padding::NoPadding);
+
+            let cipher_bytes = ecb.encrypt(&aes, &plain_bytes).unwrap();
+            assert_eq!(cipher_bytes, cipher.from_hex().unwrap());
+            assert_eq!(ecb.decrypt(&aes, &cipher_bytes).unwrap(), plain_bytes);
+        }
+    }
+
+    #[test]
+    fn test_aes_ecb_sp800_38a() {
+        let plain_bytes = SP800_38A_PLAIN.from_hex().unwrap();
+
+        for (key, cipher) in SP800_38A_KEYS.iter()
+            .zip(SP800_38A_ECB_CIPHERS) {
+
+            let key = key.from_hex().unwrap();
+            let expected = cipher.from_hex().unwrap();
+
+            // Encryptor adds a full block of padding at the end
+            let cipher_bytes = Aes128EcbEncryptor.encrypt(&plain_bytes, &key)
+                .unwrap();
+            assert_eq!(&cipher_bytes[..64], expected.as_slice());
+
+            let decrypted = Aes128EcbDecryptor.decrypt(&cipher_bytes, &key)
+                .unwrap();
+            assert_eq!(decrypted, plain_bytes);
+        }
+    }
+
+    #[test]
+    fn test_aes_cbc_sp800_38a() {
+        let plain_bytes = SP800_38A_PLAIN.from_hex().unwrap();
+        let iv = SP800_38A_IV.from_hex().unwrap();
+
+        for (key, cipher) in SP800_38A_KEYS.iter()
+            .zip(SP800_38A_CBC_CIPHERS) {
+
+            let key = key.from_hex().unwrap();
+            let expected = cipher.from_hex().unwrap();
+
+            let cipher_bytes = Aes128CbcEncryptor(&iv)
+                .encrypt(&plain_bytes, &key)
+                .unwrap();
+            assert_eq!(&cipher_bytes[..64], expected.as_slice());
+
+            let decrypted = Aes128CbcDecryptor(&iv)
+                .decrypt(&cipher_bytes, &key)
+                .unwrap();
+            assert_eq!(decrypted, plain_bytes);
+        }
+    }
+
+    #[test]
+    fn test_aes_ctr_sp800_38a_all_key_sizes() {
+        let plain_bytes = SP800_38A_PLAIN.from_hex().unwrap();
+        let nonce = SP800_38A_NONCE.from_hex().unwrap();
+
+        for (key, cipher) in SP800_38A_KEYS.iter()
+            .zip(SP800_38A_CTR_CIPHERS) {
+
+            let key = key.from_hex().unwrap();
+            let cryptor = Aes128CtrCryptor::with_counter(&nonce,
+                CounterFormat::BigEndian32, SP800_38A_COUNTER);
+
+            let cipher_bytes = cryptor.encrypt(&plain_bytes, &key).unwrap();
+            assert_eq!(cipher_bytes, cipher.from_hex().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_aes_ctr_encrypt_sp800_38a() {
+        let key = SP800_38A_KEY.from_hex().unwrap();
+        let nonce = SP800_38A_NONCE.from_hex().unwrap();
+        let plain_bytes = SP800_38A_PLAIN.from_hex().unwrap();
+
+        let cryptor = Aes128CtrCryptor::with_counter(&nonce,
+            CounterFormat::BigEndian32, SP800_38A_COUNTER);
+        let cipher_bytes = cryptor.encrypt(&plain_bytes, &key).unwrap();
+
+        assert_eq!(cipher_bytes, SP800_38A_CIPHER.from_hex().unwrap());
+    }
+
+    #[test]
+    fn test_aes_ctr_decrypt_sp800_38a() {
+        let key = SP800_38A_KEY.from_hex().unwrap();
+        let nonce = SP800_38A_NONCE.from_hex().unwrap();
+        let cipher_bytes = SP800_38A_CIPHER.from_hex().unwrap();
+
+        let cryptor = Aes128CtrCryptor::with_counter(&nonce,
+            CounterFormat::BigEndian32, SP800_38A_COUNTER);
+        let plain_bytes = cryptor.decrypt(&cipher_bytes, &key).unwrap();
+
+        assert_eq!(plain_bytes, SP800_38A_PLAIN.from_hex().unwrap());
+    }
+
+    #[test]
+    fn test_aes_ctr_message_not_block_aligned() {
+        let key = SP800_38A_KEY.from_hex().unwrap();
+        let nonce = SP800_38A_NONCE.from_hex().unwrap();
+        let plain_bytes = SP800_38A_PLAIN.from_hex().unwrap();
+
+        let cryptor = Aes128CtrCryptor::with_counter(&nonce,
+            CounterFormat::BigEndian32, SP800_38A_COUNTER);
+        let cipher_bytes = cryptor.encrypt(&plain_bytes[..21], &key).unwrap();
+
+        let expected = SP800_38A_CIPHER.from_hex().unwrap();
+        assert_eq!(cipher_bytes.as_slice(), &expected[..21]);
+    }
+
+    #[test]
+    fn test_aes_ctr_counter_wraps_within_32_bits() {
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let nonce = &[7; 12];
+
+        let cryptor = Aes128CtrCryptor::with_counter(nonce,
+            CounterFormat::BigEndian32, 0xffffffff);
+        let cipher_bytes = cryptor.encrypt(&[0; 32], key).unwrap();
+
+        // Second block should have rolled over to counter 0
+        let mut counter_block = nonce.to_vec();
+        counter_block.extend_from_slice(&[0, 0, 0, 0]);
+        let expected = Aes128EcbEncryptor.encrypt(&counter_block, key)
+            .unwrap();
+        assert_eq!(&cipher_bytes[16..], &expected[..16]);
+    }
+
+    #[test]
+    fn test_aes_ctr_cryptopals() {
+        let cipher_bytes = &[47, 190, 231, 107, 249, 235, 22, 194, 175, 202,
+            119, 122, 31, 51, 168, 27, 177, 135, 76, 181, 236, 77, 91, 189,
+            170, 246, 63, 218, 204, 139, 95, 56, 79, 193, 236, 178, 49, 50,
+            84, 46, 239, 250, 254, 69, 215, 208, 164, 175, 160, 226, 210, 21];
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let nonce = &[0; 8];
+
+        let cryptor = Aes128CtrCryptor::new(nonce,
+            CounterFormat::LittleEndian64);
+        let plain_bytes = cryptor.decrypt(cipher_bytes, key).unwrap();
+
+        let expected = "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ";
+        assert_eq!(plain_bytes.as_slice(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_aes_ctr_invalid_nonce() {
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let cryptor = Aes128CtrCryptor::new(&[0; 12],
+            CounterFormat::LittleEndian64);
+
+        match cryptor.encrypt("foo".as_bytes(), key) {
+            Err(CryptoError::InvalidIvLength(12)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_aes_ecb_invalid_key_length() {
+        let key = "YELLOW".as_bytes();
+
+        match Aes128EcbEncryptor.encrypt("foo".as_bytes(), key) {
+            Err(CryptoError::InvalidKeyLength(6)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_aes_cbc_invalid_iv_length() {
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let iv = "GREEN".as_bytes();
+
+        match Aes128CbcEncryptor(iv).encrypt("foo".as_bytes(), key) {
+            Err(CryptoError::InvalidIvLength(5)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_aes_cbc_decrypt_not_block_aligned() {
+        let cipher_bytes = &[118, 250, 12, 228, 185, 89, 251, 169, 77, 66];
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let iv = "GREEN SPACECRAFT".as_bytes();
+
+        match Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key) {
+            Err(CryptoError::NotBlockAligned(10)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_aes_cbc_decrypt_padding_error() {
+        let cipher_bytes = &[118, 250, 12, 228, 185, 89, 251, 169, 77, 66, 236,
+            123, 185, 11, 6, 134];
+        let key = "YELLOW SUBMARINE".as_bytes();
+
+        // Wrong IV garbles the padding byte of the only block
+        let iv = "GREEN SPACECRAF!".as_bytes();
+
+        match Aes128CbcDecryptor(iv).decrypt(cipher_bytes, key) {
+            Err(CryptoError::Padding(_)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_mt19937_cryptor_round_trip() {
+        let plain_bytes = b"Seeded with only sixteen bits".to_vec();
+        let key = [0x12, 0x34];
+
+        let cipher_bytes = Mt19937Cryptor.encrypt(&plain_bytes, &key)
+            .unwrap();
+        assert!(cipher_bytes != plain_bytes);
+
+        let output = Mt19937Cryptor.decrypt(&cipher_bytes, &key).unwrap();
+        assert_eq!(output, plain_bytes);
+    }
+
+    #[test]
+    fn test_mt19937_cryptor_keystream() {
+        // Key 0x1571 is the seed 5489, first output 3499211612
+        let output = Mt19937Cryptor.encrypt(&[0; 4], &[0x15, 0x71]).unwrap();
+        assert_eq!(output, vec![0xd0, 0x91, 0xbb, 0x5c]);
+    }
+
+    #[test]
+    fn test_mt19937_cryptor_invalid_key_length() {
+        match Mt19937Cryptor.encrypt(b"foo", &[1, 2, 3]) {
+            Err(CryptoError::InvalidKeyLength(3)) => {},
+            _ => panic!("Expected InvalidKeyLength")
+        }
+    }
 }
@@ -0,0 +1,294 @@
+// The challenges only run in the 1536 bit group. The larger RFC 3526
+// groups are here for anyone who wants a realistic size.
+#![allow(dead_code)]
+
+use libs::bignum;
+use libs::bignum::BigUint;
+use libs::hmac::Hash;
+use libs::random::Random;
+
+// MODP primes of RFC 3526, all with generator 2. The 1536 bit one is what
+// Challenge 33 calls the NIST prime.
+const MODP_1536: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff";
+
+const MODP_2048: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b\
+    e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718\
+    3995497cea956ae515d2261898fa051015728e5a8aacaa68ffffffffffffffff";
+
+const MODP_3072: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b\
+    e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718\
+    3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33\
+    a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7\
+    abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864\
+    d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2\
+    08e24fa074e5ab3143db5bfce0fd108e4b82d120a93ad2caffffffffffffffff";
+
+const MODP_4096: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b\
+    e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718\
+    3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33\
+    a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7\
+    abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864\
+    d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2\
+    08e24fa074e5ab3143db5bfce0fd108e4b82d120a92108011a723c12a787e6d7\
+    88719a10bdba5b2699c327186af4e23c1a946834b6150bda2583e9ca2ad44ce8\
+    dbbbc2db04de8ef92e8efc141fbecaa6287c59474e6bc05d99b2964fa090c3a2\
+    233ba186515be7ed1f612970cee2d7afb81bdd762170481cd0069127d5b05aa9\
+    93b4ea988d8fddc186ffb7dc90a6c08f4df435c934063199ffffffffffffffff";
+
+const MODP_6144: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b\
+    e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718\
+    3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33\
+    a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7\
+    abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864\
+    d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2\
+    08e24fa074e5ab3143db5bfce0fd108e4b82d120a92108011a723c12a787e6d7\
+    88719a10bdba5b2699c327186af4e23c1a946834b6150bda2583e9ca2ad44ce8\
+    dbbbc2db04de8ef92e8efc141fbecaa6287c59474e6bc05d99b2964fa090c3a2\
+    233ba186515be7ed1f612970cee2d7afb81bdd762170481cd0069127d5b05aa9\
+    93b4ea988d8fddc186ffb7dc90a6c08f4df435c93402849236c3fab4d27c7026\
+    c1d4dcb2602646dec9751e763dba37bdf8ff9406ad9e530ee5db382f413001ae\
+    b06a53ed9027d831179727b0865a8918da3edbebcf9b14ed44ce6cbaced4bb1b\
+    db7f1447e6cc254b332051512bd7af426fb8f401378cd2bf5983ca01c64b92ec\
+    f032ea15d1721d03f482d7ce6e74fef6d55e702f46980c82b5a84031900b1c9e\
+    59e7c97fbec7e8f323a97a7e36cc88be0f1d45b7ff585ac54bd407b22b4154aa\
+    cc8f6d7ebf48e1d814cc5ed20f8037e0a79715eef29be32806a1d58bb7c5da76\
+    f550aa3d8a1fbff0eb19ccb1a313d55cda56c9ec2ef29632387fe8d76e3c0468\
+    043e8f663f4860ee12bf2d5b0b7474d6e694f91e6dcc4024ffffffffffffffff";
+
+const MODP_8192: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b\
+    e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718\
+    3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33\
+    a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7\
+    abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864\
+    d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2\
+    08e24fa074e5ab3143db5bfce0fd108e4b82d120a92108011a723c12a787e6d7\
+    88719a10bdba5b2699c327186af4e23c1a946834b6150bda2583e9ca2ad44ce8\
+    dbbbc2db04de8ef92e8efc141fbecaa6287c59474e6bc05d99b2964fa090c3a2\
+    233ba186515be7ed1f612970cee2d7afb81bdd762170481cd0069127d5b05aa9\
+    93b4ea988d8fddc186ffb7dc90a6c08f4df435c93402849236c3fab4d27c7026\
+    c1d4dcb2602646dec9751e763dba37bdf8ff9406ad9e530ee5db382f413001ae\
+    b06a53ed9027d831179727b0865a8918da3edbebcf9b14ed44ce6cbaced4bb1b\
+    db7f1447e6cc254b332051512bd7af426fb8f401378cd2bf5983ca01c64b92ec\
+    f032ea15d1721d03f482d7ce6e74fef6d55e702f46980c82b5a84031900b1c9e\
+    59e7c97fbec7e8f323a97a7e36cc88be0f1d45b7ff585ac54bd407b22b4154aa\
+    cc8f6d7ebf48e1d814cc5ed20f8037e0a79715eef29be32806a1d58bb7c5da76\
+    f550aa3d8a1fbff0eb19ccb1a313d55cda56c9ec2ef29632387fe8d76e3c0468\
+    043e8f663f4860ee12bf2d5b0b7474d6e694f91e6dbe115974a3926f12fee5e4\
+    38777cb6a932df8cd8bec4d073b931ba3bc832b68d9dd300741fa7bf8afc47ed\
+    2576f6936ba424663aab639c5ae4f5683423b4742bf1c978238f16cbe39d652d\
+    e3fdb8befc848ad922222e04a4037c0713eb57a81a23f0c73473fc646cea306b\
+    4bcbc8862f8385ddfa9d4b7fa2c087e879683303ed5bdd3a062b3cf5b3a278a6\
+    6d2a13f83f44f82ddf310ee074ab6a364597e899a0255dc164f31cc50846851d\
+    f9ab48195ded7ea1b1d510bd7ee74d73faf36bc31ecfa268359046f4eb879f92\
+    4009438b481c6cd7889a002ed5ee382bc9190da6fc026e479558e4475677e9aa\
+    9e3050e2765694dfc81f56e880b96e7160c980dd98edd3dfffffffffffffffff";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    pub p: BigUint,
+    pub g: BigUint,
+}
+
+pub struct KeyPair {
+    pub private: BigUint,
+    pub public: BigUint,
+}
+
+impl Group {
+    pub fn new(p: BigUint, g: BigUint) -> Self {
+        Group { p, g }
+    }
+
+    pub fn modp_1536() -> Self {
+        Group::modp(MODP_1536)
+    }
+
+    pub fn modp_2048() -> Self {
+        Group::modp(MODP_2048)
+    }
+
+    pub fn modp_3072() -> Self {
+        Group::modp(MODP_3072)
+    }
+
+    pub fn modp_4096() -> Self {
+        Group::modp(MODP_4096)
+    }
+
+    pub fn modp_6144() -> Self {
+        Group::modp(MODP_6144)
+    }
+
+    pub fn modp_8192() -> Self {
+        Group::modp(MODP_8192)
+    }
+
+    fn modp(hex: &str) -> Self {
+        Group::new(bignum::from_hex(hex).unwrap(), BigUint::from(2u32))
+    }
+
+    pub fn keypair(&self, random: &mut Random) -> KeyPair {
+        let private = random.rand_bignum_range(&BigUint::from(2u32),
+            &(&self.p - 1u32));
+        let public = self.public_key(&private);
+
+        KeyPair { private, public }
+    }
+
+    pub fn public_key(&self, private: &BigUint) -> BigUint {
+        bignum::mod_pow(&self.g, private, &self.p)
+    }
+
+    pub fn shared_secret(&self, private: &BigUint, public: &BigUint)
+        -> BigUint {
+
+        bignum::mod_pow(public, private, &self.p)
+    }
+}
+
+// First 16 bytes of the hash of the secret's big endian bytes, an
+// AES-128 key
+pub fn aes_key<H: Hash>(secret: &BigUint) -> Vec<u8> {
+    H::digest(&bignum::to_bytes(secret))[..16].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libs::cryptor::{Aes128CbcDecryptor, Aes128CbcEncryptor, Decryptor,
+        Encryptor};
+    use libs::sha1::Sha1;
+    use libs::sha1;
+    use libs::sha256::Sha256;
+
+    #[test]
+    fn test_shared_secret_small() {
+        // Example of the Wikipedia article
+        let group = Group::new(BigUint::from(23u32), BigUint::from(5u32));
+        let (a, b) = (BigUint::from(6u32), BigUint::from(15u32));
+
+        assert_eq!(group.public_key(&a), BigUint::from(8u32));
+        assert_eq!(group.public_key(&b), BigUint::from(19u32));
+        assert_eq!(group.shared_secret(&a, &BigUint::from(19u32)),
+            BigUint::from(2u32));
+        assert_eq!(group.shared_secret(&b, &BigUint::from(8u32)),
+            BigUint::from(2u32));
+    }
+
+    #[test]
+    fn test_modp_groups() {
+        let ones = (BigUint::from(1u32) << 64) - 1u32;
+
+        for (group, bits) in [(Group::modp_1536(), 1536),
+            (Group::modp_2048(), 2048), (Group::modp_3072(), 3072),
+            (Group::modp_4096(), 4096), (Group::modp_6144(), 6144),
+            (Group::modp_8192(), 8192)] {
+
+            assert_eq!(group.p.bits(), bits);
+            assert_eq!(group.g, BigUint::from(2u32));
+
+            // Each starts and ends with 64 one bits
+            assert_eq!(&group.p & &ones, ones);
+            assert_eq!(&group.p >> (bits - 64), ones);
+        }
+
+        // Safe primes, so 2 generates the subgroup of order (p - 1)/2. The
+        // larger groups take too long to check this way.
+        for group in [Group::modp_1536(), Group::modp_2048(),
+            Group::modp_3072()] {
+
+            let q = (&group.p - 1u32) >> 1;
+            assert_eq!(bignum::mod_pow(&group.g, &q, &group.p),
+                BigUint::from(1u32));
+        }
+    }
+
+    #[test]
+    fn test_keypair() {
+        let mut random = Random::new();
+        let group = Group::modp_1536();
+        let keypair = group.keypair(&mut random);
+
+        assert!(keypair.private > BigUint::from(1u32));
+        assert!(keypair.private < group.p);
+        assert_eq!(keypair.public, group.public_key(&keypair.private));
+    }
+
+    #[test]
+    fn test_shared_secret() {
+        let mut random = Random::new();
+
+        for group in &[Group::modp_1536(), Group::modp_2048()] {
+            let alice = group.keypair(&mut random);
+            let bob = group.keypair(&mut random);
+
+            assert_eq!(group.shared_secret(&alice.private, &bob.public),
+                group.shared_secret(&bob.private, &alice.public));
+        }
+    }
+
+    #[test]
+    fn test_aes_key() {
+        let secret = BigUint::from(0x0102u32);
+
+        assert_eq!(aes_key::<Sha1>(&secret), &sha1::sha1(&[1, 2])[..16]);
+        assert_eq!(aes_key::<Sha256>(&secret).len(), 16);
+        assert!(aes_key::<Sha1>(&secret) != aes_key::<Sha256>(&secret));
+    }
+
+    #[test]
+    fn test_aes_key_round_trip() {
+        let mut random = Random::new();
+        let group = Group::modp_1536();
+        let alice = group.keypair(&mut random);
+        let bob = group.keypair(&mut random);
+
+        let alice_key = aes_key::<Sha256>(
+            &group.shared_secret(&alice.private, &bob.public));
+        let bob_key = aes_key::<Sha256>(
+            &group.shared_secret(&bob.private, &alice.public));
+
+        let iv = [0; 16];
+        let cipher = Aes128CbcEncryptor(&iv)
+            .encrypt(b"YELLOW SUBMARINE", &alice_key).unwrap();
+
+        assert_eq!(Aes128CbcDecryptor(&iv).decrypt(&cipher, &bob_key)
+            .unwrap(), b"YELLOW SUBMARINE");
+    }
+}
@@ -0,0 +1,126 @@
+use libs::sha1;
+use libs::sha1::Sha1;
+use libs::md4;
+use libs::md4::Md4;
+use libs::sha256;
+use libs::sha256::Sha256;
+
+// What HMAC needs to know about a hash
+pub trait Hash {
+    fn block_size() -> usize;
+    fn digest(bytes: &[u8]) -> Vec<u8>;
+}
+
+impl Hash for Sha1 {
+    fn block_size() -> usize {
+        sha1::BLOCK_SIZE
+    }
+
+    fn digest(bytes: &[u8]) -> Vec<u8> {
+        sha1::sha1(bytes).to_vec()
+    }
+}
+
+impl Hash for Md4 {
+    fn block_size() -> usize {
+        md4::BLOCK_SIZE
+    }
+
+    fn digest(bytes: &[u8]) -> Vec<u8> {
+        md4::md4(bytes).to_vec()
+    }
+}
+
+impl Hash for Sha256 {
+    fn block_size() -> usize {
+        sha256::BLOCK_SIZE
+    }
+
+    fn digest(bytes: &[u8]) -> Vec<u8> {
+        sha256::sha256(bytes).to_vec()
+    }
+}
+
+// HMAC as in RFC 2104
+pub fn hmac<H: Hash>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut block_key = if key.len() > H::block_size() {
+        H::digest(key)
+    } else {
+        key.to_vec()
+    };
+    block_key.resize(H::block_size(), 0);
+
+    let mut inner = block_key.iter().map(|byte| byte ^ 0x36)
+        .collect::<Vec<u8>>();
+    inner.extend(message);
+
+    let mut outer = block_key.iter().map(|byte| byte ^ 0x5c)
+        .collect::<Vec<u8>>();
+    outer.extend(H::digest(&inner));
+
+    H::digest(&outer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::hex::ToHex;
+    use crypto;
+    use crypto::mac::Mac;
+    use libs::random::Random;
+
+    #[test]
+    fn test_hmac_sha1_rfc_2202() {
+        assert_eq!(hmac::<Sha1>(&[0x0b; 20], b"Hi There").to_hex(),
+            "b617318655057264e28bc0b6fb378c8ef146be00");
+        assert_eq!(hmac::<Sha1>(b"Jefe", b"what do ya want for nothing?")
+            .to_hex(), "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+    }
+
+    #[test]
+    fn test_hmac_sha1_rfc_2202_long_key() {
+        let message = b"Test Using Larger Than Block-Size Key - Hash Key First";
+        assert_eq!(hmac::<Sha1>(&[0xaa; 80], message).to_hex(),
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112");
+    }
+
+    #[test]
+    fn test_hmac_sha1_against_rust_crypto() {
+        let mut random = Random::new();
+
+        for key_len in &[0, 1, 20, 63, 64, 65, 100] {
+            let key = &mut vec![0; *key_len];
+            let message = &mut [0; 100];
+            random.fill_bytes(key);
+            random.fill_bytes(message);
+
+            let mut expected = crypto::hmac::Hmac::new(
+                crypto::sha1::Sha1::new(), key);
+            expected.input(message);
+
+            assert_eq!(hmac::<Sha1>(key, message),
+                expected.result().code().to_vec());
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc_4231() {
+        assert_eq!(hmac::<Sha256>(&[0x0b; 20], b"Hi There").to_hex(),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(hmac::<Sha256>(b"Jefe", b"what do ya want for nothing?")
+            .to_hex(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[test]
+    fn test_hmac_md4() {
+        // Computed with an independent MD4 through Python's hmac module
+        let message = b"The quick brown fox jumps over the lazy dog";
+        assert_eq!(hmac::<Md4>(b"key", message).to_hex(),
+            "8d3366c440a9c65124ab0b5f4ca27338");
+
+        let message = b"Test Using Larger Than Block-Size Key - Hash Key First";
+        assert_eq!(hmac::<Md4>(&[0xaa; 80], message).to_hex(),
+            "545b8f2577657042df628fbb98430d5f");
+    }
+}
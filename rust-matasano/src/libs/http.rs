@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use rustc_serialize::hex::FromHex;
+
+use libs::oracle::HmacOracle;
+
+// Just enough HTTP/1.1 for the timing attack: GET requests, a status code
+// back and no body either way. Query values are taken as they come, with
+// no percent decoding.
+pub struct Request {
+    pub path: String,
+    pub params: HashMap<String, String>,
+}
+
+pub fn parse_request_line(line: &str) -> Option<Request> {
+    let mut parts = line.split_whitespace();
+
+    if parts.next() != Some("GET") {
+        return None;
+    }
+
+    let target = parts.next()?;
+    let mut target_parts = target.splitn(2, '?');
+    let path = target_parts.next()?.to_string();
+
+    let params = target_parts.next()
+        .unwrap_or("")
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let mut key_value = param.splitn(2, '=');
+            let key = key_value.next().unwrap_or("").to_string();
+            let value = key_value.next().unwrap_or("").to_string();
+            (key, value)
+        })
+        .collect();
+
+    Some(Request { path, params })
+}
+
+// How long a client gets to send its request line
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Serves one connection at a time, so requests do not get in the way of
+// each other's timing. Accepting never runs out of connections, so this
+// only returns with the process.
+pub fn serve<F>(listener: &TcpListener, handler: F)
+    where F: Fn(&Request) -> u16 {
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue
+        };
+
+        // A client that stalls or hangs up early only loses its own
+        // request
+        let _ = respond(stream, &handler);
+    }
+}
+
+fn respond<F>(mut stream: TcpStream, handler: &F) -> io::Result<()>
+    where F: Fn(&Request) -> u16 {
+
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let status = match parse_request_line(&line) {
+        Some(request) => handler(&request),
+        None => 400
+    };
+
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Length: 0\r\n\
+        Connection: close\r\n\r\n", status, reason(status))
+}
+
+// /test?file=foo&signature=<hex HMAC of foo>
+pub fn serve_hmac(listener: &TcpListener, hmac_oracle: &HmacOracle) {
+    serve(listener, |request| {
+        if request.path != "/test" {
+            return 404;
+        }
+
+        let file = request.params.get("file");
+        let signature = request.params.get("signature")
+            .and_then(|signature| signature.from_hex().ok());
+
+        match (file, signature) {
+            (Some(file), Some(signature)) =>
+                if hmac_oracle.verify(file.as_bytes(), &signature) {
+                    200
+                } else {
+                    500
+                },
+            _ => 400
+        }
+    })
+}
+
+// Sends a GET and returns the status code
+pub fn get<A: ToSocketAddrs>(addr: A, target: &str) -> io::Result<u16> {
+    let mut stream = TcpStream::connect(addr)?;
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\
+        Connection: close\r\n\r\n", target)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    response.split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+            "Malformed response"))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        _ => "Internal Server Error"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::sync::Arc;
+    use rustc_serialize::hex::ToHex;
+
+    #[test]
+    fn test_parse_request_line() {
+        let request = parse_request_line(
+            "GET /test?file=foo&signature=46b4ec58 HTTP/1.1\r\n").unwrap();
+
+        assert_eq!(request.path, "/test");
+        assert_eq!(request.params.get("file").unwrap(), "foo");
+        assert_eq!(request.params.get("signature").unwrap(), "46b4ec58");
+    }
+
+    #[test]
+    fn test_parse_request_line_no_query() {
+        let request = parse_request_line("GET / HTTP/1.1").unwrap();
+
+        assert_eq!(request.path, "/");
+        assert!(request.params.is_empty());
+    }
+
+    #[test]
+    fn test_parse_request_line_not_get() {
+        assert!(parse_request_line("POST /test HTTP/1.1").is_none());
+        assert!(parse_request_line("").is_none());
+    }
+
+    #[test]
+    fn test_serve_hmac() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hmac_oracle = Arc::new(HmacOracle::new(Duration::from_millis(0)));
+
+        let server_oracle = hmac_oracle.clone();
+        thread::spawn(move || serve_hmac(&listener, &server_oracle));
+
+        let signature = hmac_oracle.hmac(b"foo").to_hex();
+        let valid = format!("/test?file=foo&signature={}", signature);
+        let invalid = format!("/test?file=bar&signature={}", signature);
+
+        assert_eq!(get(addr, &valid).unwrap(), 200);
+        assert_eq!(get(addr, &invalid).unwrap(), 500);
+        assert_eq!(get(addr, "/test?file=foo").unwrap(), 400);
+        assert_eq!(get(addr, "/other").unwrap(), 404);
+    }
+
+    #[test]
+    fn test_serve_survives_bad_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(&listener, |_| 200));
+
+        // Hangs up without a request, then sends one and hangs up without
+        // reading the response
+        drop(TcpStream::connect(addr).unwrap());
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET / HTTP/1.1\r\n\r\n").unwrap();
+        drop(stream);
+
+        assert_eq!(get(addr, "/").unwrap(), 200);
+    }
+}
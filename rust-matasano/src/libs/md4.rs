@@ -0,0 +1,226 @@
+// Kept whole like Sha1, though the MAC forgery only needs the hasher
+#![allow(dead_code)]
+
+use libs::sha1;
+
+const INITIAL_STATE: [u32; 4] =
+    [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+pub const BLOCK_SIZE: usize = 64;
+pub const DIGEST_SIZE: usize = 16;
+
+// MD4 as in RFC 1320, with the same resumable state as Sha1
+#[derive(Clone)]
+pub struct Md4 {
+    state: [u32; 4],
+    // Bytes hashed so far, including any before the state was taken over
+    len: u64,
+    buffer: Vec<u8>,
+}
+
+impl Md4 {
+    pub fn new() -> Self {
+        Md4::from_state(INITIAL_STATE, 0)
+    }
+
+    // Picks up hashing after len bytes, which have to be a whole number
+    // of blocks, padding included
+    pub fn from_state(state: [u32; 4], len: u64) -> Self {
+        Md4 { state, len, buffer: Vec::with_capacity(BLOCK_SIZE) }
+    }
+
+    // Takes over from a finished hash of len bytes, padding included
+    pub fn from_digest(digest: &[u8; DIGEST_SIZE], len: u64) -> Self {
+        let mut state = [0; 4];
+        for (index, word) in digest.chunks(4).enumerate() {
+            state[index] = to_word(word);
+        }
+
+        Md4::from_state(state, len)
+    }
+
+    pub fn state(&self) -> [u32; 4] {
+        self.state
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+
+        let state = &mut self.state;
+        sha1::md_update(&mut self.buffer, bytes, |block| compress(state, block));
+    }
+
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        let padding = md_padding(self.len);
+        let len = self.len;
+        self.update(&padding);
+        self.len = len;
+
+        let mut digest = [0; DIGEST_SIZE];
+        for (index, word) in self.state.iter().enumerate() {
+            digest[index * 4..index * 4 + 4]
+                .copy_from_slice(&word.to_le_bytes());
+        }
+
+        digest
+    }
+}
+
+impl Default for Md4 {
+    fn default() -> Self {
+        Md4::new()
+    }
+}
+
+pub fn md4(bytes: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut md4 = Md4::new();
+    md4.update(bytes);
+    md4.finalize()
+}
+
+pub fn secret_prefix_mac(key: &[u8], message: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut md4 = Md4::new();
+    md4.update(key);
+    md4.update(message);
+    md4.finalize()
+}
+
+// Same as SHA-1 padding, except the length goes in little endian
+pub fn md_padding(len: u64) -> Vec<u8> {
+    let zeroes = (BLOCK_SIZE * 2 - 9 - len as usize % BLOCK_SIZE) %
+        BLOCK_SIZE;
+
+    let mut padding = vec![0x80];
+    padding.extend(vec![0; zeroes]);
+    padding.extend_from_slice(&(len.wrapping_mul(8)).to_le_bytes());
+    padding
+}
+
+pub fn md_pad(bytes: &[u8]) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.extend(md_padding(bytes.len() as u64));
+    padded
+}
+
+// Boolean function, added constant, word order and shifts of a round
+type Round = (fn(u32, u32, u32) -> u32, u32, [usize; 16], [u32; 4]);
+
+fn to_word(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) | ((bytes[1] as u32) << 8) |
+        ((bytes[2] as u32) << 16) | ((bytes[3] as u32) << 24)
+}
+
+fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut x = [0u32; 16];
+    for (index, word) in block.chunks(4).enumerate() {
+        x[index] = to_word(word);
+    }
+
+    let mut v = *state;
+
+    let rounds: [Round; 3] = [
+        (|x, y, z| (x & y) | (!x & z), 0,
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+            [3, 7, 11, 19]),
+        (|x, y, z| (x & y) | (x & z) | (y & z), 0x5a827999,
+            [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+            [3, 5, 9, 13]),
+        (|x, y, z| x ^ y ^ z, 0x6ed9eba1,
+            [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15],
+            [3, 9, 11, 15]),
+    ];
+
+    for &(f, k, order, shifts) in rounds.iter() {
+        for step in 0..16 {
+            // Steps update a, d, c, b in turn, each from the other three
+            let a = (4 - step % 4) % 4;
+            let (b, c, d) = ((a + 1) % 4, (a + 2) % 4, (a + 3) % 4);
+
+            v[a] = v[a].wrapping_add(f(v[b], v[c], v[d]))
+                .wrapping_add(x[order[step]])
+                .wrapping_add(k)
+                .rotate_left(shifts[step % 4]);
+        }
+    }
+
+    for (word, value) in state.iter_mut().zip(v.iter()) {
+        *word = word.wrapping_add(*value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::hex::ToHex;
+
+    #[test]
+    fn test_md4_rfc_1320() {
+        // Test suite of RFC 1320 appendix A.5
+        let vectors = [
+            ("", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+            ("a", "bde52cb31de33e46245e05fbdbd6fb24"),
+            ("abc", "a448017aaf21d8525fc10ae87aa6729d"),
+            ("message digest", "d9130a8164549fe818874806e1c7014b"),
+            ("abcdefghijklmnopqrstuvwxyz",
+                "d79e1c308aa5bbcdeea8ed63df412da9"),
+            ("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "043f8582f241db351ce627e153e7f0e4"),
+            ("1234567890123456789012345678901234567890\
+                1234567890123456789012345678901234567890",
+                "e33b4ddc9c38f2199c3e7b164fcc0536"),
+        ];
+
+        for &(input, digest) in vectors.iter() {
+            assert_eq!(md4(input.as_bytes()).to_hex(), digest);
+        }
+    }
+
+    #[test]
+    fn test_md4_streaming() {
+        let bytes = (0..300).map(|byte| byte as u8).collect::<Vec<u8>>();
+
+        let mut md4 = Md4::new();
+        for chunk in bytes.chunks(7) {
+            md4.update(chunk);
+        }
+
+        assert_eq!(md4.finalize(), super::md4(&bytes));
+    }
+
+    #[test]
+    fn test_md4_from_state() {
+        let bytes = [b'x'; 150];
+
+        let mut first = Md4::new();
+        first.update(&bytes[..128]);
+
+        let mut resumed = Md4::from_state(first.state(), first.len());
+        resumed.update(&bytes[128..]);
+
+        assert_eq!(resumed.finalize(), md4(&bytes));
+    }
+
+    #[test]
+    fn test_md4_from_digest() {
+        let bytes = md_pad(b"YELLOW SUBMARINE");
+
+        let mut resumed = Md4::from_digest(&md4(b"YELLOW SUBMARINE"),
+            bytes.len() as u64);
+        resumed.update(b"suffix");
+
+        let mut expected = bytes.clone();
+        expected.extend(b"suffix");
+        assert_eq!(resumed.finalize(), md4(&expected));
+    }
+
+    #[test]
+    fn test_md_padding() {
+        let padding = md_padding(3);
+        assert_eq!(padding.len(), 61);
+        assert_eq!(&padding[53..], &[24, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
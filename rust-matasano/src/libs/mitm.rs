@@ -0,0 +1,186 @@
+use libs::bignum::BigUint;
+use libs::dh::Group;
+use libs::protocol;
+use libs::protocol::{Direction, Message, ProtocolError, Strategy};
+use libs::random::Random;
+
+// Challenge 34. With p in place of both public keys, both sides end up
+// with p^x mod p = 0 as the secret, and so does Mallory.
+pub struct KeyInjection {
+    p: Option<BigUint>,
+    pub plains: Vec<Vec<u8>>,
+}
+
+impl KeyInjection {
+    pub fn new() -> Self {
+        KeyInjection { p: None, plains: Vec::new() }
+    }
+}
+
+impl Default for KeyInjection {
+    fn default() -> Self {
+        KeyInjection::new()
+    }
+}
+
+impl Strategy for KeyInjection {
+    fn intercept(&mut self, direction: Direction, message: Message)
+        -> Result<Message, ProtocolError> {
+
+        match message {
+            Message::Group(group) => {
+                self.p = Some(group.p.clone());
+                Ok(Message::Group(group))
+            },
+            Message::PublicKey(_) => {
+                let p = self.p.clone()
+                    .ok_or(ProtocolError::UnexpectedMessage)?;
+                Ok(Message::PublicKey(p))
+            },
+            Message::Cipher(cipher) => {
+                if direction == Direction::ToBob {
+                    self.plains.push(protocol::decrypt_message(
+                        &BigUint::from(0u32), &cipher)?);
+                }
+                Ok(Message::Cipher(cipher))
+            },
+            message => Ok(message)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FakeGenerator {
+    One,
+    P,
+    PMinusOne,
+}
+
+// Challenge 35. Bob is handed a fake generator, and the same in place of
+// Alice's public key, which makes his secret g^b, the very public key he
+// sends back. With g = 1 or p that is 1 or 0, and so is Alice's secret.
+// With g = p - 1 it is 1 or p - 1, so Alice gets 1 in its place instead.
+// Mallory reads and re-encrypts both ways, so neither side notices.
+pub struct GroupInjection {
+    generator: FakeGenerator,
+    random: Random,
+    fake: Option<BigUint>,
+    bob_secret: Option<BigUint>,
+    alice_secret: Option<BigUint>,
+    pub plains: Vec<Vec<u8>>,
+}
+
+impl GroupInjection {
+    pub fn new(generator: FakeGenerator) -> Self {
+        GroupInjection { generator, random: Random::new(), fake: None,
+            bob_secret: None, alice_secret: None, plains: Vec::new() }
+    }
+}
+
+impl Strategy for GroupInjection {
+    fn intercept(&mut self, direction: Direction, message: Message)
+        -> Result<Message, ProtocolError> {
+
+        match (direction, message) {
+            (Direction::ToBob, Message::Group(group)) => {
+                let fake = match self.generator {
+                    FakeGenerator::One => BigUint::from(1u32),
+                    FakeGenerator::P => group.p.clone(),
+                    FakeGenerator::PMinusOne => &group.p - 1u32
+                };
+
+                self.fake = Some(fake.clone());
+                Ok(Message::Group(Group::new(group.p, fake)))
+            },
+            (Direction::ToBob, Message::PublicKey(_)) => {
+                let fake = self.fake.clone()
+                    .ok_or(ProtocolError::UnexpectedMessage)?;
+                Ok(Message::PublicKey(fake))
+            },
+            (Direction::ToAlice, Message::PublicKey(public)) => {
+                // 0 and 1 stay the same under any power
+                let forwarded = match self.generator {
+                    FakeGenerator::PMinusOne => BigUint::from(1u32),
+                    _ => public.clone()
+                };
+
+                self.alice_secret = Some(forwarded.clone());
+                self.bob_secret = Some(public);
+                Ok(Message::PublicKey(forwarded))
+            },
+            (Direction::ToBob, Message::Cipher(cipher)) => {
+                let alice_secret = self.alice_secret.clone()
+                    .ok_or(ProtocolError::UnexpectedMessage)?;
+                let plain = protocol::decrypt_message(&alice_secret, &cipher)?;
+                let bob_secret = self.bob_secret.clone()
+                    .ok_or(ProtocolError::UnexpectedMessage)?;
+                let cipher = protocol::encrypt_message(&bob_secret, &plain,
+                    &mut self.random)?;
+
+                self.plains.push(plain);
+                Ok(Message::Cipher(cipher))
+            },
+            (Direction::ToAlice, Message::Cipher(cipher)) => {
+                let bob_secret = self.bob_secret.clone()
+                    .ok_or(ProtocolError::UnexpectedMessage)?;
+                let alice_secret = self.alice_secret.clone()
+                    .ok_or(ProtocolError::UnexpectedMessage)?;
+                let plain = protocol::decrypt_message(&bob_secret, &cipher)?;
+
+                Ok(Message::Cipher(protocol::encrypt_message(&alice_secret,
+                    &plain, &mut self.random)?))
+            },
+            (_, message) => Ok(message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dh_messages() -> Vec<Vec<u8>> {
+        vec![b"YELLOW SUBMARINE".to_vec(), b"Meet me at noon".to_vec(),
+            b"Bring the key".to_vec()]
+    }
+
+    #[test]
+    fn test_key_injection() {
+        let mut key_injection = KeyInjection::new();
+        let transcript = protocol::run_session(Group::modp_1536(),
+            &dh_messages(), &mut key_injection).unwrap();
+
+        assert_eq!(transcript.echoes, dh_messages());
+        assert_eq!(transcript.received, dh_messages());
+        assert_eq!(key_injection.plains, dh_messages());
+    }
+
+    #[test]
+    fn test_group_injection() {
+        for &generator in &[FakeGenerator::One, FakeGenerator::P,
+            FakeGenerator::PMinusOne] {
+
+            let mut group_injection = GroupInjection::new(generator);
+            let transcript = protocol::run_session(Group::modp_1536(),
+                &dh_messages(), &mut group_injection).unwrap();
+
+            assert_eq!(transcript.echoes, dh_messages());
+            assert_eq!(transcript.received, dh_messages());
+            assert_eq!(group_injection.plains, dh_messages());
+        }
+    }
+
+    #[test]
+    fn test_group_injection_p_minus_one_both_parities() {
+        // Each run Bob's private key is odd or even with even odds, which
+        // decides between 1 and p - 1 for his public key
+        for _ in 0..8 {
+            let mut group_injection =
+                GroupInjection::new(FakeGenerator::PMinusOne);
+            protocol::run_session(Group::modp_1536(), &dh_messages(),
+                &mut group_injection).unwrap();
+
+            assert_eq!(group_injection.plains, dh_messages());
+        }
+    }
+}
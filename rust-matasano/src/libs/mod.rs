@@ -0,0 +1,24 @@
+pub mod utils;
+pub mod cryptor;
+pub mod aes;
+pub mod modes;
+pub mod pkcs7;
+pub mod random;
+pub mod bignum;
+pub mod dh;
+pub mod protocol;
+pub mod mitm;
+pub mod srp;
+pub mod simple_srp;
+pub mod mt19937;
+pub mod clock;
+pub mod sha1;
+pub mod md4;
+pub mod sha256;
+pub mod hmac;
+pub mod http;
+pub mod timing;
+pub mod analyzer;
+pub mod oracle;
+pub mod keysize;
+pub mod english;
@@ -0,0 +1,552 @@
+// The challenges only need ECB and CBC with PKCS#7 padding. PCBC, CFB, OFB
+// and unpadded input are here for completeness.
+#![allow(dead_code)]
+
+use libs::aes::Aes;
+use libs::cryptor::BlockCipher;
+use libs::cryptor::CryptoError;
+use libs::cryptor::Decryptor;
+use libs::cryptor::Encryptor;
+use libs::cryptor::xor;
+use libs::pkcs7;
+
+pub type Iv<const N: usize> = [u8; N];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Padding {
+    Pkcs7,
+    NoPadding,
+}
+
+impl Padding {
+    fn pad(&self, bytes: &[u8], block_size: usize) -> Vec<u8> {
+        match *self {
+            Padding::Pkcs7 => pkcs7::pad(bytes, block_size as u8),
+            Padding::NoPadding => bytes.to_vec(),
+        }
+    }
+
+    fn unpad(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+        match *self {
+            Padding::Pkcs7 => Ok(pkcs7::unpad(&bytes)?),
+            Padding::NoPadding => Ok(bytes),
+        }
+    }
+}
+
+// A way of chaining the blocks of a BlockCipher with an N byte block.
+// Modes carry their own IV and padding policy, the cipher (and so the
+// key) is passed in for each call.
+pub trait BlockMode<const N: usize> {
+    fn encrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError>;
+
+    fn decrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError>;
+}
+
+pub struct Ecb {
+    pub padding: Padding,
+}
+
+pub struct Cbc<const N: usize> {
+    pub iv: Iv<N>,
+    pub padding: Padding,
+}
+
+pub struct Pcbc<const N: usize> {
+    pub iv: Iv<N>,
+    pub padding: Padding,
+}
+
+// CFB and OFB turn the block cipher into a stream cipher, so without
+// padding the last block is allowed to be short.
+pub struct Cfb<const N: usize> {
+    pub iv: Iv<N>,
+    pub padding: Padding,
+}
+
+pub struct Ofb<const N: usize> {
+    pub iv: Iv<N>,
+    pub padding: Padding,
+}
+
+impl Ecb {
+    pub fn new(padding: Padding) -> Self {
+        Ecb { padding }
+    }
+}
+
+impl<const N: usize> Cbc<N> {
+    pub fn new(iv: Iv<N>, padding: Padding) -> Self {
+        Cbc { iv, padding }
+    }
+}
+
+impl<const N: usize> Pcbc<N> {
+    pub fn new(iv: Iv<N>, padding: Padding) -> Self {
+        Pcbc { iv, padding }
+    }
+}
+
+impl<const N: usize> Cfb<N> {
+    pub fn new(iv: Iv<N>, padding: Padding) -> Self {
+        Cfb { iv, padding }
+    }
+}
+
+impl<const N: usize> Ofb<N> {
+    pub fn new(iv: Iv<N>, padding: Padding) -> Self {
+        Ofb { iv, padding }
+    }
+}
+
+pub fn to_iv<const N: usize>(bytes: &[u8]) -> Result<Iv<N>, CryptoError> {
+    match bytes.len() {
+        len if len == N => Ok(to_block(bytes)),
+        len => Err(CryptoError::InvalidIvLength(len))
+    }
+}
+
+pub fn to_block<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut block = [0; N];
+    block.copy_from_slice(bytes);
+    block
+}
+
+fn xor_block<const N: usize>(block1: &[u8; N], block2: &[u8; N]) -> [u8; N] {
+    let mut block = *block1;
+
+    for (byte, other) in block.iter_mut().zip(block2.iter()) {
+        *byte ^= other;
+    }
+
+    block
+}
+
+fn check_block_aligned(bytes: &[u8], block_size: usize)
+    -> Result<(), CryptoError> {
+
+    match bytes.len() {
+        len if len % block_size != 0 =>
+            Err(CryptoError::NotBlockAligned(len)),
+        _ => Ok(())
+    }
+}
+
+// Stream modes only need aligned input when padding is in use
+fn check_stream_aligned(bytes: &[u8], block_size: usize, padding: &Padding)
+    -> Result<(), CryptoError> {
+
+    match *padding {
+        Padding::Pkcs7 => check_block_aligned(bytes, block_size),
+        Padding::NoPadding => Ok(())
+    }
+}
+
+impl<const N: usize> BlockMode<N> for Ecb {
+    fn encrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.padding.pad(bytes, N);
+        check_block_aligned(&plain_bytes, N)?;
+
+        Ok(plain_bytes.chunks(N)
+            .flat_map(|chunk| cipher.encrypt_block(&to_block(chunk)).to_vec())
+            .collect())
+    }
+
+    fn decrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        check_block_aligned(bytes, N)?;
+
+        let plain_bytes = bytes.chunks(N)
+            .flat_map(|chunk| cipher.decrypt_block(&to_block(chunk)).to_vec())
+            .collect();
+
+        self.padding.unpad(plain_bytes)
+    }
+}
+
+impl<const N: usize> BlockMode<N> for Cbc<N> {
+    fn encrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.padding.pad(bytes, N);
+        check_block_aligned(&plain_bytes, N)?;
+
+        let mut prev = self.iv;
+        let mut cipher_bytes = Vec::with_capacity(plain_bytes.len());
+
+        for chunk in plain_bytes.chunks(N) {
+            prev = cipher.encrypt_block(&xor_block(&prev, &to_block(chunk)));
+            cipher_bytes.extend_from_slice(&prev);
+        }
+
+        Ok(cipher_bytes)
+    }
+
+    fn decrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        check_block_aligned(bytes, N)?;
+
+        let mut prev = self.iv;
+        let mut plain_bytes = Vec::with_capacity(bytes.len());
+
+        for chunk in bytes.chunks(N) {
+            let block = to_block(chunk);
+            plain_bytes.extend_from_slice(
+                &xor_block(&prev, &cipher.decrypt_block(&block)));
+            prev = block;
+        }
+
+        self.padding.unpad(plain_bytes)
+    }
+}
+
+// Propagating CBC, both the previous plain and cipher blocks are mixed
+// into the next block. IV takes the place of their xor for the first
+// block.
+impl<const N: usize> BlockMode<N> for Pcbc<N> {
+    fn encrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.padding.pad(bytes, N);
+        check_block_aligned(&plain_bytes, N)?;
+
+        let mut prev = self.iv;
+        let mut cipher_bytes = Vec::with_capacity(plain_bytes.len());
+
+        for chunk in plain_bytes.chunks(N) {
+            let plain_block = to_block(chunk);
+            let cipher_block = cipher.encrypt_block(
+                &xor_block(&prev, &plain_block));
+
+            cipher_bytes.extend_from_slice(&cipher_block);
+            prev = xor_block(&plain_block, &cipher_block);
+        }
+
+        Ok(cipher_bytes)
+    }
+
+    fn decrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        check_block_aligned(bytes, N)?;
+
+        let mut prev = self.iv;
+        let mut plain_bytes = Vec::with_capacity(bytes.len());
+
+        for chunk in bytes.chunks(N) {
+            let cipher_block = to_block(chunk);
+            let plain_block = xor_block(&prev,
+                &cipher.decrypt_block(&cipher_block));
+
+            plain_bytes.extend_from_slice(&plain_block);
+            prev = xor_block(&plain_block, &cipher_block);
+        }
+
+        self.padding.unpad(plain_bytes)
+    }
+}
+
+// Full block cipher feedback, the previous cipher block is encrypted to
+// get the keystream for the next one
+impl<const N: usize> BlockMode<N> for Cfb<N> {
+    fn encrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.padding.pad(bytes, N);
+        let mut prev = self.iv;
+        let mut cipher_bytes = Vec::with_capacity(plain_bytes.len());
+
+        for chunk in plain_bytes.chunks(N) {
+            let keystream = cipher.encrypt_block(&prev);
+            let cipher_chunk = xor(chunk, &keystream);
+
+            // Only a short last chunk misses the feedback, and there is
+            // no block after it
+            if cipher_chunk.len() == N {
+                prev = to_block(&cipher_chunk);
+            }
+
+            cipher_bytes.extend(cipher_chunk);
+        }
+
+        Ok(cipher_bytes)
+    }
+
+    fn decrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        check_stream_aligned(bytes, N, &self.padding)?;
+
+        let mut prev = self.iv;
+        let mut plain_bytes = Vec::with_capacity(bytes.len());
+
+        for chunk in bytes.chunks(N) {
+            let keystream = cipher.encrypt_block(&prev);
+            plain_bytes.extend(xor(chunk, &keystream));
+
+            if chunk.len() == N {
+                prev = to_block(chunk);
+            }
+        }
+
+        self.padding.unpad(plain_bytes)
+    }
+}
+
+impl<const N: usize> Ofb<N> {
+    fn crypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8]) -> Vec<u8> {
+        let mut keystream = self.iv;
+        let mut output = Vec::with_capacity(bytes.len());
+
+        for chunk in bytes.chunks(N) {
+            keystream = cipher.encrypt_block(&keystream);
+            output.extend(xor(chunk, &keystream));
+        }
+
+        output
+    }
+}
+
+impl<const N: usize> BlockMode<N> for Ofb<N> {
+    fn encrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.padding.pad(bytes, N);
+        Ok(self.crypt(cipher, &plain_bytes))
+    }
+
+    fn decrypt<C: BlockCipher<N>>(&self, cipher: &C, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        check_stream_aligned(bytes, N, &self.padding)?;
+        self.padding.unpad(self.crypt(cipher, bytes))
+    }
+}
+
+// Plugs any AES block mode into the Encryptor and Decryptor traits,
+// with the key size picked from the key
+pub struct AesCryptor<M>(pub M);
+
+impl<M: BlockMode<16>> Encryptor for AesCryptor<M> {
+    fn encrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        self.0.encrypt(&Aes::new(key)?, bytes)
+    }
+}
+
+impl<M: BlockMode<16>> Decryptor for AesCryptor<M> {
+    fn decrypt(&self, bytes: &[u8], key: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        self.0.decrypt(&Aes::new(key)?, bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::hex::FromHex;
+
+    // NIST SP 800-38A, F.3.13 (CFB128) and F.4.1 (OFB) for AES-128
+    const SP800_38A_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const SP800_38A_IV: &str = "000102030405060708090a0b0c0d0e0f";
+    const SP800_38A_PLAIN: &str = "6bc1bee22e409f96e93d7e117393172a\
+        ae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52ef\
+        f69f2445df4f9b17ad2b417be66c3710";
+    const SP800_38A_CFB_CIPHER: &str = "3b3fd92eb72dad20333449f8e83cfb4a\
+        c8a64537a0b3a93fcde3cdad9f1ce58b26751f67a3cbb140b1808cf187a4f4df\
+        c04b05357c5d1c0eeac4c66f9ff7f2e6";
+    const SP800_38A_OFB_CIPHER: &str = "3b3fd92eb72dad20333449f8e83cfb4a\
+        7789508d16918f03f53c52dac54ed8259740051e9c5fecf64344f7a82260edcc\
+        304c6528f659c77866a510d9c1d6ae5e";
+
+    // Toy cipher with an 8 byte block, just enough to show the modes do
+    // not depend on AES or its block size
+    struct XorCipher([u8; 8]);
+
+    impl BlockCipher<8> for XorCipher {
+        fn encrypt_block(&self, block: &[u8; 8]) -> [u8; 8] {
+            let mut output = xor_block(block, &self.0);
+            output.rotate_left(1);
+            output
+        }
+
+        fn decrypt_block(&self, block: &[u8; 8]) -> [u8; 8] {
+            let mut output = *block;
+            output.rotate_right(1);
+            xor_block(&output, &self.0)
+        }
+    }
+
+    fn sp800_38a() -> (Aes, Iv<16>, Vec<u8>) {
+        let aes = Aes::new(&SP800_38A_KEY.from_hex().unwrap()).unwrap();
+        let iv = to_iv(&SP800_38A_IV.from_hex().unwrap()).unwrap();
+        (aes, iv, SP800_38A_PLAIN.from_hex().unwrap())
+    }
+
+    #[test]
+    fn test_cfb_sp800_38a() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+        let cfb = Cfb::new(iv, Padding::NoPadding);
+
+        let cipher_bytes = cfb.encrypt(&aes, &plain_bytes).unwrap();
+        assert_eq!(cipher_bytes, SP800_38A_CFB_CIPHER.from_hex().unwrap());
+        assert_eq!(cfb.decrypt(&aes, &cipher_bytes).unwrap(), plain_bytes);
+    }
+
+    #[test]
+    fn test_ofb_sp800_38a() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+        let ofb = Ofb::new(iv, Padding::NoPadding);
+
+        let cipher_bytes = ofb.encrypt(&aes, &plain_bytes).unwrap();
+        assert_eq!(cipher_bytes, SP800_38A_OFB_CIPHER.from_hex().unwrap());
+        assert_eq!(ofb.decrypt(&aes, &cipher_bytes).unwrap(), plain_bytes);
+    }
+
+    #[test]
+    fn test_stream_modes_short_last_block() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+        let plain_bytes = &plain_bytes[..37];
+
+        let cfb_bytes = Cfb::new(iv, Padding::NoPadding)
+            .encrypt(&aes, plain_bytes)
+            .unwrap();
+        let ofb_bytes = Ofb::new(iv, Padding::NoPadding)
+            .encrypt(&aes, plain_bytes)
+            .unwrap();
+
+        assert_eq!(cfb_bytes, &SP800_38A_CFB_CIPHER.from_hex().unwrap()[..37]);
+        assert_eq!(ofb_bytes, &SP800_38A_OFB_CIPHER.from_hex().unwrap()[..37]);
+    }
+
+    #[test]
+    fn test_cbc_no_padding_not_block_aligned() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+
+        let cbc = Cbc::new(iv, Padding::NoPadding);
+
+        match cbc.encrypt(&aes, &plain_bytes[..20]) {
+            Err(CryptoError::NotBlockAligned(20)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let (aes, iv, _) = sp800_38a();
+
+        assert_eq!(Ecb::new(Padding::NoPadding).encrypt(&aes, &[]).unwrap(),
+            Vec::<u8>::new());
+        assert_eq!(Cbc::new(iv, Padding::NoPadding).decrypt(&aes, &[])
+            .unwrap(), Vec::<u8>::new());
+
+        // Padded, empty plain text is a whole block and empty cipher text
+        // has no padding to strip
+        assert_eq!(Cbc::new(iv, Padding::Pkcs7).encrypt(&aes, &[]).unwrap()
+            .len(), 16);
+        match Cbc::new(iv, Padding::Pkcs7).decrypt(&aes, &[]) {
+            Err(CryptoError::Padding(_)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn test_pcbc_round_trip() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+        let pcbc = Pcbc::new(iv, Padding::Pkcs7);
+
+        let cipher_bytes = pcbc.encrypt(&aes, &plain_bytes[..50]).unwrap();
+        assert_eq!(cipher_bytes.len(), 64);
+        assert_eq!(pcbc.decrypt(&aes, &cipher_bytes).unwrap(),
+            &plain_bytes[..50]);
+    }
+
+    #[test]
+    fn test_pcbc_first_block_matches_cbc() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+
+        let pcbc_bytes = Pcbc::new(iv, Padding::NoPadding)
+            .encrypt(&aes, &plain_bytes)
+            .unwrap();
+        let cbc_bytes = Cbc::new(iv, Padding::NoPadding)
+            .encrypt(&aes, &plain_bytes)
+            .unwrap();
+
+        assert_eq!(&pcbc_bytes[..16], &cbc_bytes[..16]);
+        assert!(pcbc_bytes[16..] != cbc_bytes[16..]);
+    }
+
+    #[test]
+    fn test_pcbc_swapped_blocks_recover() {
+        let (aes, iv, plain_bytes) = sp800_38a();
+        let pcbc = Pcbc::new(iv, Padding::NoPadding);
+        let mut cipher_bytes = pcbc.encrypt(&aes, &plain_bytes).unwrap();
+
+        // Swapping two adjacent blocks garbles them, but PCBC gets back
+        // in sync for the blocks after
+        let (first, second) = cipher_bytes.split_at_mut(32);
+        first[16..].swap_with_slice(&mut second[..16]);
+
+        let decrypted = pcbc.decrypt(&aes, &cipher_bytes).unwrap();
+        assert!(decrypted[16..48] != plain_bytes[16..48]);
+        assert_eq!(&decrypted[48..], &plain_bytes[48..]);
+    }
+
+    #[test]
+    fn test_modes_generic_block_size() {
+        let cipher = XorCipher(*b"8bytekey");
+        let iv = *b"8byteiv!";
+        let plain_bytes = "Modes are generic over the block size".as_bytes();
+
+        let ecb = Ecb::new(Padding::Pkcs7);
+        let cbc = Cbc::new(iv, Padding::Pkcs7);
+        let pcbc = Pcbc::new(iv, Padding::Pkcs7);
+        let cfb = Cfb::new(iv, Padding::NoPadding);
+        let ofb = Ofb::new(iv, Padding::NoPadding);
+
+        let cipher_bytes = ecb.encrypt(&cipher, plain_bytes).unwrap();
+        assert_eq!(cipher_bytes.len(), 40);
+        assert_eq!(ecb.decrypt(&cipher, &cipher_bytes).unwrap(), plain_bytes);
+
+        let cipher_bytes = cbc.encrypt(&cipher, plain_bytes).unwrap();
+        assert_eq!(cbc.decrypt(&cipher, &cipher_bytes).unwrap(), plain_bytes);
+
+        let cipher_bytes = pcbc.encrypt(&cipher, plain_bytes).unwrap();
+        assert_eq!(pcbc.decrypt(&cipher, &cipher_bytes).unwrap(), plain_bytes);
+
+        let cipher_bytes = cfb.encrypt(&cipher, plain_bytes).unwrap();
+        assert_eq!(cipher_bytes.len(), plain_bytes.len());
+        assert_eq!(cfb.decrypt(&cipher, &cipher_bytes).unwrap(), plain_bytes);
+
+        let cipher_bytes = ofb.encrypt(&cipher, plain_bytes).unwrap();
+        assert_eq!(ofb.decrypt(&cipher, &cipher_bytes).unwrap(), plain_bytes);
+    }
+
+    #[test]
+    fn test_aes_cryptor() {
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let iv = to_iv(b"GREEN SPACECRAFT").unwrap();
+        let cryptor = AesCryptor(Ofb::new(iv, Padding::Pkcs7));
+
+        let cipher_bytes = cryptor.encrypt(b"PURPLE SPEEDBOAT", key).unwrap();
+        assert_eq!(cipher_bytes.len(), 32);
+        assert_eq!(cryptor.decrypt(&cipher_bytes, key).unwrap(),
+            b"PURPLE SPEEDBOAT");
+    }
+
+    #[test]
+    fn test_invalid_iv_length() {
+        match to_iv::<16>(b"GREEN") {
+            Err(CryptoError::InvalidIvLength(5)) => {},
+            result => panic!("Unexpected result {:?}", result)
+        }
+    }
+}
@@ -0,0 +1,437 @@
+use rand::Rng;
+
+pub const N: usize = 624;
+pub const M: usize = 397;
+const MATRIX_A: u32 = 0x9908b0df;
+const UPPER_MASK: u32 = 0x80000000;
+const LOWER_MASK: u32 = 0x7fffffff;
+
+const N_64: usize = 312;
+const M_64: usize = 156;
+const MATRIX_A_64: u64 = 0xb5026f5aa96619e9;
+const UPPER_MASK_64: u64 = 0xffffffff80000000;
+const LOWER_MASK_64: u64 = 0x7fffffff;
+
+// 32 bit Mersenne Twister, as in the reference mt19937ar.c
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0; N];
+        state[0] = seed;
+
+        for i in 1..N {
+            let prev = state[i - 1];
+            state[i] = 1812433253u32.wrapping_mul(prev ^ (prev >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        Mt19937 { state, index: N }
+    }
+
+    // An empty key seeds the same as a single zero
+    pub fn from_array(key: &[u32]) -> Self {
+        let key = if key.is_empty() { &[0][..] } else { key };
+        let mut mt = Mt19937::new(19650218);
+        let state = &mut mt.state;
+        let mut i = 1;
+        let mut j = 0;
+
+        for _ in 0..N.max(key.len()) {
+            let prev = state[i - 1];
+            state[i] = (state[i] ^ (prev ^ (prev >> 30)).wrapping_mul(1664525))
+                .wrapping_add(key[j])
+                .wrapping_add(j as u32);
+
+            i += 1;
+            j += 1;
+
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+
+        for _ in 0..(N - 1) {
+            let prev = state[i - 1];
+            state[i] = (state[i] ^ (prev ^ (prev >> 30))
+                .wrapping_mul(1566083941))
+                .wrapping_sub(i as u32);
+
+            i += 1;
+
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+        }
+
+        // Makes sure the initial state is not all zeroes
+        state[0] = UPPER_MASK;
+        mt
+    }
+
+    // Picks up a generator whose twist has updated the state words below
+    // index, but not the rest. Next output is the word at index.
+    pub fn from_state(state: [u32; N], index: usize) -> Self {
+        assert!(index < N);
+
+        let mut mt = Mt19937 { state, index: N };
+        mt.twist_from(index);
+        mt.index = index;
+        mt
+    }
+
+    pub fn extract(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+
+        let value = self.state[self.index];
+        self.index += 1;
+
+        temper(value)
+    }
+
+    // Consecutive outputs as big endian bytes, the last output cut short
+    // to fit len
+    pub fn extract_bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(len + 3);
+        while bytes.len() < len {
+            let value = self.extract();
+            bytes.extend(&[(value >> 24) as u8, (value >> 16) as u8,
+                (value >> 8) as u8, value as u8]);
+        }
+
+        bytes.truncate(len);
+        bytes
+    }
+
+    fn twist(&mut self) {
+        self.twist_from(0);
+        self.index = 0;
+    }
+
+    fn twist_from(&mut self, start: usize) {
+        for i in start..N {
+            self.state[i] = twist_word(self.state[i],
+                self.state[(i + 1) % N], self.state[(i + M) % N]);
+        }
+    }
+}
+
+// New value of a state word from its old value, the word next to it and
+// the word M places ahead
+pub fn twist_word(word: u32, next_word: u32, ahead_word: u32) -> u32 {
+    let value = (word & UPPER_MASK) | (next_word & LOWER_MASK);
+    let mut next = value >> 1;
+
+    if value & 1 == 1 {
+        next ^= MATRIX_A;
+    }
+
+    ahead_word ^ next
+}
+
+pub fn temper(value: u32) -> u32 {
+    let mut y = value;
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c5680;
+    y ^= (y << 15) & 0xefc60000;
+    y ^ (y >> 18)
+}
+
+pub fn untemper(value: u32) -> u32 {
+    let mut y = value;
+    y = undo_right_shift(y, 18);
+    y = undo_left_shift(y, 15, 0xefc60000);
+    y = undo_left_shift(y, 7, 0x9d2c5680);
+    undo_right_shift(y, 11)
+}
+
+// Inverts y ^= y >> shift, recovering shift bits at a time from the top
+fn undo_right_shift(value: u32, shift: u32) -> u32 {
+    let mut y = value;
+    for _ in 0..(32 / shift) {
+        y = value ^ (y >> shift);
+    }
+    y
+}
+
+// Inverts y ^= (y << shift) & mask, recovering shift bits at a time from
+// the bottom
+fn undo_left_shift(value: u32, shift: u32, mask: u32) -> u32 {
+    let mut y = value;
+    for _ in 0..(32 / shift) {
+        y = value ^ ((y << shift) & mask);
+    }
+    y
+}
+
+impl Rng for Mt19937 {
+    fn next_u32(&mut self) -> u32 {
+        self.extract()
+    }
+}
+
+// 64 bit Mersenne Twister, as in the reference mt19937-64.c
+pub struct Mt19937_64 {
+    state: [u64; N_64],
+    index: usize,
+}
+
+impl Mt19937_64 {
+    pub fn new(seed: u64) -> Self {
+        let mut state = [0; N_64];
+        state[0] = seed;
+
+        for i in 1..N_64 {
+            let prev = state[i - 1];
+            state[i] = 6364136223846793005u64.wrapping_mul(prev ^ (prev >> 62))
+                .wrapping_add(i as u64);
+        }
+
+        Mt19937_64 { state, index: N_64 }
+    }
+
+    // An empty key seeds the same as a single zero
+    pub fn from_array(key: &[u64]) -> Self {
+        let key = if key.is_empty() { &[0][..] } else { key };
+        let mut mt = Mt19937_64::new(19650218);
+        let state = &mut mt.state;
+        let mut i = 1;
+        let mut j = 0;
+
+        for _ in 0..N_64.max(key.len()) {
+            let prev = state[i - 1];
+            state[i] = (state[i] ^ (prev ^ (prev >> 62))
+                .wrapping_mul(3935559000370003845))
+                .wrapping_add(key[j])
+                .wrapping_add(j as u64);
+
+            i += 1;
+            j += 1;
+
+            if i >= N_64 {
+                state[0] = state[N_64 - 1];
+                i = 1;
+            }
+
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+
+        for _ in 0..(N_64 - 1) {
+            let prev = state[i - 1];
+            state[i] = (state[i] ^ (prev ^ (prev >> 62))
+                .wrapping_mul(2862933555777941757))
+                .wrapping_sub(i as u64);
+
+            i += 1;
+
+            if i >= N_64 {
+                state[0] = state[N_64 - 1];
+                i = 1;
+            }
+        }
+
+        state[0] = 1 << 63;
+        mt
+    }
+
+    pub fn extract(&mut self) -> u64 {
+        if self.index >= N_64 {
+            self.twist();
+        }
+
+        let mut y = self.state[self.index];
+        self.index += 1;
+
+        y ^= (y >> 29) & 0x5555555555555555;
+        y ^= (y << 17) & 0x71d67fffeda60000;
+        y ^= (y << 37) & 0xfff7eee000000000;
+        y ^ (y >> 43)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N_64 {
+            let value = (self.state[i] & UPPER_MASK_64) |
+                (self.state[(i + 1) % N_64] & LOWER_MASK_64);
+            let mut next = value >> 1;
+
+            if value & 1 == 1 {
+                next ^= MATRIX_A_64;
+            }
+
+            self.state[i] = self.state[(i + M_64) % N_64] ^ next;
+        }
+
+        self.index = 0;
+    }
+}
+
+impl Rng for Mt19937_64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.extract() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mt19937_default_seed() {
+        let mut mt = Mt19937::new(5489);
+        let outputs = (0..5).map(|_| mt.extract()).collect::<Vec<u32>>();
+
+        assert_eq!(outputs, vec![3499211612, 581869302, 3890346734,
+            3586334585, 545404204]);
+    }
+
+    #[test]
+    fn test_mt19937_10000th_output() {
+        // C++11 requires this of std::mt19937
+        let mut mt = Mt19937::new(5489);
+        let output = (0..10000).map(|_| mt.extract()).last().unwrap();
+
+        assert_eq!(output, 4123659995);
+    }
+
+    #[test]
+    fn test_mt19937_from_array() {
+        // First outputs of mt19937ar.out
+        let mut mt = Mt19937::from_array(&[0x123, 0x234, 0x345, 0x456]);
+        let outputs = (0..5).map(|_| mt.extract()).collect::<Vec<u32>>();
+
+        assert_eq!(outputs, vec![1067595299, 955945823, 477289528,
+            4107218783, 4228976476]);
+    }
+
+    #[test]
+    fn test_mt19937_from_empty_array() {
+        let mut empty = Mt19937::from_array(&[]);
+        let mut zero = Mt19937::from_array(&[0]);
+
+        for _ in 0..1000 {
+            assert_eq!(empty.extract(), zero.extract());
+        }
+    }
+
+    #[test]
+    fn test_mt19937_64_10000th_output() {
+        // C++11 requires this of std::mt19937_64
+        let mut mt = Mt19937_64::new(5489);
+        let output = (0..10000).map(|_| mt.extract()).last().unwrap();
+
+        assert_eq!(output, 9981545732273789042);
+    }
+
+    #[test]
+    fn test_mt19937_64_from_array() {
+        // First outputs of mt19937-64.out
+        let mut mt = Mt19937_64::from_array(&[0x12345, 0x23456, 0x34567,
+            0x45678]);
+        let outputs = (0..5).map(|_| mt.extract()).collect::<Vec<u64>>();
+
+        assert_eq!(outputs, vec![7266447313870364031, 4946485549665804864,
+            16945909448695747420, 16394063075524226720, 4873882236456199058]);
+    }
+
+    #[test]
+    fn test_mt19937_64_from_empty_array() {
+        let mut empty = Mt19937_64::from_array(&[]);
+        let mut zero = Mt19937_64::from_array(&[0]);
+
+        for _ in 0..1000 {
+            assert_eq!(empty.extract(), zero.extract());
+        }
+    }
+
+    #[test]
+    fn test_mt19937_extract_bytes() {
+        let mut mt = Mt19937::new(5489);
+
+        // 3499211612 and 581869302
+        assert_eq!(mt.extract_bytes(6), vec![0xd0, 0x91, 0xbb, 0x5c, 0x22,
+            0xae]);
+    }
+
+    #[test]
+    fn test_untemper_single_bits() {
+        // Tempering only xors shifted and masked copies of the word, so it
+        // is linear over GF(2). Round tripping every single bit covers all
+        // 2^32 words.
+        for bit in 0..32 {
+            let value = 1u32 << bit;
+            assert_eq!(untemper(temper(value)), value);
+            assert_eq!(temper(untemper(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_untemper_round_trip() {
+        // Every 16 bit pattern, in both halves of the word, plus a stride
+        // across the full range
+        for half in 0..0x10000u32 {
+            for &value in &[half, half << 16, half.wrapping_mul(0x9e3779b9)] {
+                assert_eq!(untemper(temper(value)), value);
+                assert_eq!(temper(untemper(value)), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_untemper_extremes() {
+        for &value in &[0, 1, 0x7fffffff, 0x80000000, 0xffffffff] {
+            assert_eq!(untemper(temper(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_mt19937_from_state() {
+        let mut first = Mt19937::new(1234);
+        first.extract();
+
+        let mut second = Mt19937::new(1234);
+        for _ in 0..(N + 1) {
+            second.extract();
+        }
+
+        // Twist halfway through, with 10 words of the second block in
+        let mut state = first.state;
+        state[..10].copy_from_slice(&second.state[..10]);
+
+        let mut expected = Mt19937::new(1234);
+        for _ in 0..(N + 10) {
+            expected.extract();
+        }
+
+        let mut resumed = Mt19937::from_state(state, 10);
+        for _ in 0..1000 {
+            assert_eq!(resumed.extract(), expected.extract());
+        }
+    }
+
+    #[test]
+    fn test_mt19937_same_seed_same_outputs() {
+        let mut mt1 = Mt19937::new(42);
+        let mut mt2 = Mt19937::new(42);
+
+        for _ in 0..1000 {
+            assert_eq!(mt1.extract(), mt2.extract());
+        }
+    }
+}
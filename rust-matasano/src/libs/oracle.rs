@@ -1,3 +1,6 @@
+use std::thread;
+use std::time::Duration;
+
 use libs::random::Random;
 use libs::cryptor::Encryptor;
 use libs::cryptor::Decryptor;
@@ -5,6 +8,16 @@ use libs::cryptor::Aes128EcbEncryptor;
 use libs::cryptor::Aes128EcbDecryptor;
 use libs::cryptor::Aes128CbcEncryptor;
 use libs::cryptor::Aes128CbcDecryptor;
+use libs::cryptor::CryptoError;
+use libs::cryptor::Mt19937Cryptor;
+use libs::cryptor::Aes128CtrCryptor;
+use libs::cryptor::CounterFormat;
+use libs::clock::Clock;
+use libs::mt19937::Mt19937;
+use libs::sha1;
+use libs::md4;
+use libs::hmac;
+use libs::sha1::Sha1;
 
 pub struct Oracle {
     random: Random,
@@ -25,6 +38,15 @@ impl Oracle {
         Oracle::new_oracle(Some(random.rand_bytes(&256)), suffix, random)
     }
 
+    // Some systems save on an IV by reusing the key (Challenge 27)
+    pub fn key_as_iv(prefix: Option<Vec<u8>>, suffix: Option<Vec<u8>>)
+        -> Self {
+
+        let mut oracle = Oracle::new(prefix, suffix);
+        oracle.iv = oracle.key.clone();
+        oracle
+    }
+
     fn new_oracle(prefix: Option<Vec<u8>>, suffix: Option<Vec<u8>>,
         mut random: Random) -> Self {
 
@@ -52,28 +74,478 @@ impl Oracle {
         plain_bytes
     }
 
-    pub fn encrypt(&self, bytes: &[u8]) -> Vec<u8> {
+    pub fn encrypt(&self, bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
         let plain_bytes = self.get_plain_bytes(bytes);
         Aes128EcbEncryptor.encrypt(&plain_bytes, &self.key)
     }
 
-    pub fn decrypt(&self, bytes: &[u8]) -> Vec<u8> {
+    pub fn decrypt(&self, bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
         Aes128EcbDecryptor.decrypt(bytes, &self.key)
     }
 
-    pub fn encrypt_random(&mut self, bytes: &[u8]) -> Vec<u8> {
+    pub fn encrypt_random(&mut self, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
         match self.random.rand() & 1 {
             0 => Aes128EcbEncryptor.encrypt(bytes, &self.key),
             _ => Aes128CbcEncryptor(&self.iv).encrypt(bytes, &self.key)
         }
     }
 
-    pub fn encrypt_cbc(&self, bytes: &[u8]) -> Vec<u8> {
+    pub fn encrypt_cbc(&self, bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
         let plain_bytes = self.get_plain_bytes(bytes);
         Aes128CbcEncryptor(&self.iv).encrypt(&plain_bytes, &self.key)
     }
 
-    pub fn decrypt_cbc(&self, cipher_bytes: &[u8]) -> Vec<u8> {
-        Aes128CbcDecryptor(&self.iv).decrypt(&cipher_bytes, &self.key)
+    pub fn decrypt_cbc(&self, cipher_bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        self.decrypt_cbc_with_iv(&self.iv, cipher_bytes)
+    }
+
+    // Rejects plain text with high ASCII bytes, and helpfully puts the
+    // plain text in the error
+    pub fn decrypt_cbc_ascii(&self, cipher_bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.decrypt_cbc(cipher_bytes)?;
+
+        if plain_bytes.iter().any(|&byte| byte >= 0x80) {
+            Err(CryptoError::NonAscii(plain_bytes))
+        } else {
+            Ok(plain_bytes)
+        }
+    }
+
+    // CBC IV is sent along with the cipher text, so it is not a secret
+    // and a receiver would take whatever IV comes with the message
+    #[cfg(test)]
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    pub fn decrypt_cbc_with_iv(&self, iv: &[u8], cipher_bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        Aes128CbcDecryptor(iv).decrypt(cipher_bytes, &self.key)
+    }
+
+    pub fn encrypt_ctr(&self, bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let plain_bytes = self.get_plain_bytes(bytes);
+        self.ctr_cryptor(0).encrypt(&plain_bytes, &self.key)
+    }
+
+    pub fn decrypt_ctr(&self, cipher_bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        self.ctr_cryptor(0).decrypt(cipher_bytes, &self.key)
+    }
+
+    // Random access write into a CTR cipher text, as a disk encryption
+    // layer would do it (Challenge 25). Only the keystream under the new
+    // bytes is generated, the rest of the cipher text is left alone.
+    pub fn edit(&self, cipher_bytes: &[u8], offset: usize, new_bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        if offset > cipher_bytes.len() {
+            return Err(CryptoError::InvalidOffset(offset));
+        }
+
+        // Start at the block holding offset, with filler up to it
+        let skip = offset % 16;
+        let mut plain_bytes = vec![0; skip];
+        plain_bytes.extend(new_bytes);
+
+        let encrypted = self.ctr_cryptor((offset / 16) as u64)
+            .encrypt(&plain_bytes, &self.key)?;
+
+        let mut edited = cipher_bytes[..offset].to_vec();
+        edited.extend(&encrypted[skip..]);
+
+        let end = offset + new_bytes.len();
+        if end < cipher_bytes.len() {
+            edited.extend(&cipher_bytes[end..]);
+        }
+
+        Ok(edited)
+    }
+
+    // Nonce is the first half of the IV
+    fn ctr_cryptor(&self, counter: u64) -> Aes128CtrCryptor<'_> {
+        Aes128CtrCryptor::with_counter(&self.iv[..8],
+            CounterFormat::LittleEndian64, counter)
+    }
+
+    // The MT19937 stream cipher only takes a 16 bit key, the first two
+    // bytes of the key stand in for it
+    pub fn encrypt_mt19937(&self, bytes: &[u8])
+        -> Result<Vec<u8>, CryptoError> {
+
+        let plain_bytes = self.get_plain_bytes(bytes);
+        Mt19937Cryptor.encrypt(&plain_bytes, &self.key[..2])
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum CipherMode {
+    Cbc,
+    Ctr,
+}
+
+// Challenge 16 and 26 target. User data goes into a cookie between fixed
+// comments, with ; and = quoted out so it can not add an admin field.
+pub struct CookieOracle {
+    oracle: Oracle,
+    mode: CipherMode,
+}
+
+impl CookieOracle {
+    pub fn new(mode: CipherMode) -> Self {
+        let prepend = "comment1=cooking%20MCs;userdata=";
+        let append = ";comment2=%20like%20a%20pound%20of%20bacon";
+        let oracle = Oracle::new(Some(prepend.as_bytes().to_vec()),
+            Some(append.as_bytes().to_vec()));
+
+        CookieOracle { oracle, mode }
+    }
+
+    pub fn encrypt_cookie(&self, value: &str)
+        -> Result<Vec<u8>, CryptoError> {
+
+        let quoted = value.replace(";", "\";\"")
+            .replace("=", "\"=\"");
+
+        match self.mode {
+            CipherMode::Cbc => self.oracle.encrypt_cbc(quoted.as_bytes()),
+            CipherMode::Ctr => self.oracle.encrypt_ctr(quoted.as_bytes())
+        }
+    }
+
+    pub fn is_admin(&self, cipher_bytes: &[u8]) -> Result<bool, CryptoError> {
+        let plain_bytes = match self.mode {
+            CipherMode::Cbc => self.oracle.decrypt_cbc(cipher_bytes)?,
+            CipherMode::Ctr => self.oracle.decrypt_ctr(cipher_bytes)?
+        };
+
+        Ok(String::from_utf8_lossy(&plain_bytes).contains(";admin=true;"))
+    }
+}
+
+// Signs and verifies messages with a secret-prefix MAC, hash(key ||
+// message), under a random key of random length
+pub struct MacOracle {
+    key: Vec<u8>,
+    hash: fn(&[u8], &[u8]) -> Vec<u8>,
+}
+
+impl MacOracle {
+    pub fn sha1() -> Self {
+        MacOracle::new_oracle(|key, message| {
+            sha1::secret_prefix_mac(key, message).to_vec()
+        })
+    }
+
+    pub fn md4() -> Self {
+        MacOracle::new_oracle(|key, message| {
+            md4::secret_prefix_mac(key, message).to_vec()
+        })
+    }
+
+    fn new_oracle(hash: fn(&[u8], &[u8]) -> Vec<u8>) -> Self {
+        let mut random = Random::new();
+        let key = &mut vec![0; random.rand_range(&1, &33) as usize];
+        random.fill_bytes(key);
+
+        MacOracle { key: key.to_vec(), hash }
+    }
+
+    pub fn mac(&self, message: &[u8]) -> Vec<u8> {
+        (self.hash)(&self.key, message)
+    }
+
+    pub fn verify(&self, message: &[u8], mac: &[u8]) -> bool {
+        self.mac(message) == mac
+    }
+}
+
+// Checks HMAC-SHA1 signatures of file names, leaking through timing how
+// many leading bytes of a signature are right (Challenge 31, 32)
+pub struct HmacOracle {
+    key: Vec<u8>,
+    delay: Duration,
+}
+
+impl HmacOracle {
+    pub fn new(delay: Duration) -> Self {
+        let key = &mut [0; 16];
+        Random::new().fill_bytes(key);
+
+        HmacOracle { key: key.to_vec(), delay }
+    }
+
+    pub fn hmac(&self, file: &[u8]) -> Vec<u8> {
+        hmac::hmac::<Sha1>(&self.key, file)
+    }
+
+    pub fn verify(&self, file: &[u8], signature: &[u8]) -> bool {
+        insecure_compare(&self.hmac(file), signature, self.delay)
+    }
+}
+
+// Byte at a time comparison, which sleeps after each byte that matches and
+// bails out at the first that does not
+pub fn insecure_compare(left: &[u8], right: &[u8], delay: Duration)
+    -> bool {
+
+    insecure_compare_with(left, right, || thread::sleep(delay))
+}
+
+// Same comparison with the sleep swapped for any pause
+fn insecure_compare_with<F>(left: &[u8], right: &[u8], mut pause: F) -> bool
+    where F: FnMut() {
+
+    for (left, right) in left.iter().zip(right.iter()) {
+        if left != right {
+            return false;
+        }
+
+        pause();
+    }
+
+    left.len() == right.len()
+}
+
+// Challenge 17 style target. Encrypts one of the secrets under a random
+// key and a fresh random IV, and after that will only say whether a
+// cipher text decrypts to a valid padding.
+pub struct PaddingOracle {
+    oracle: Oracle,
+    secrets: Vec<Vec<u8>>,
+}
+
+impl PaddingOracle {
+    pub fn new(secrets: Vec<Vec<u8>>) -> Self {
+        assert!(!secrets.is_empty(), "No secrets to encrypt");
+        PaddingOracle { oracle: Oracle::new(None, None), secrets }
+    }
+
+    // Returns the IV and the cipher text
+    pub fn encrypt(&mut self) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        let secrets_len = self.secrets.len() as i32;
+        let index = self.oracle.random.rand_range(&0, &secrets_len) as usize;
+        let iv = &mut [0; 16];
+        self.oracle.random.fill_bytes(iv);
+
+        let cipher_bytes = Aes128CbcEncryptor(iv)
+            .encrypt(&self.secrets[index], &self.oracle.key)?;
+
+        Ok((iv.to_vec(), cipher_bytes))
+    }
+
+    pub fn is_padding_valid(&self, iv: &[u8], cipher_bytes: &[u8]) -> bool {
+        self.oracle.decrypt_cbc_with_iv(iv, cipher_bytes).is_ok()
+    }
+}
+
+// Seeds an MT19937 with the current time for every value it hands out
+pub struct TimeSeededOracle<'a> {
+    clock: &'a dyn Clock,
+}
+
+impl<'a> TimeSeededOracle<'a> {
+    pub fn new(clock: &'a dyn Clock) -> Self {
+        TimeSeededOracle { clock }
+    }
+
+    pub fn rand(&self) -> u32 {
+        Mt19937::new(self.clock.now()).extract()
+    }
+
+    pub fn reset_token(&self, len: usize) -> Vec<u8> {
+        Mt19937::new(self.clock.now()).extract_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use libs::clock::FakeClock;
+
+    fn secrets() -> Vec<Vec<u8>> {
+        vec!["foo".as_bytes().to_vec(), "PURPLE SPEEDBOAT".as_bytes().to_vec()]
+    }
+
+    #[test]
+    fn test_padding_oracle_valid() {
+        let mut padding_oracle = PaddingOracle::new(secrets());
+
+        for _ in 0..10 {
+            let (iv, cipher_bytes) = padding_oracle.encrypt().unwrap();
+            assert_eq!(iv.len(), 16);
+            assert!(cipher_bytes.len() == 16 || cipher_bytes.len() == 32);
+            assert!(padding_oracle.is_padding_valid(&iv, &cipher_bytes));
+        }
+    }
+
+    #[test]
+    fn test_padding_oracle_fresh_iv() {
+        let mut padding_oracle = PaddingOracle::new(secrets());
+        let (iv1, _) = padding_oracle.encrypt().unwrap();
+        let (iv2, _) = padding_oracle.encrypt().unwrap();
+
+        assert!(iv1 != iv2);
+    }
+
+    #[test]
+    fn test_padding_oracle_invalid() {
+        let mut padding_oracle = PaddingOracle::new(vec![vec![1; 15]]);
+        let (mut iv, cipher_bytes) = padding_oracle.encrypt().unwrap();
+
+        // Plain text ends in \x01, this turns it into \x00
+        iv[15] ^= 1;
+        assert!(!padding_oracle.is_padding_valid(&iv, &cipher_bytes));
+        assert!(!padding_oracle.is_padding_valid(&iv, &cipher_bytes[..10]));
+    }
+
+    #[test]
+    fn test_time_seeded_oracle_rand() {
+        let clock = FakeClock::new(5489);
+        let oracle = TimeSeededOracle::new(&clock);
+
+        assert_eq!(oracle.rand(), 3499211612);
+    }
+
+    #[test]
+    fn test_time_seeded_oracle_reset_token() {
+        let clock = FakeClock::new(1500000000);
+        let oracle = TimeSeededOracle::new(&clock);
+        let token = oracle.reset_token(16);
+
+        assert_eq!(token.len(), 16);
+        assert_eq!(oracle.reset_token(16), token);
+
+        clock.sleep(1);
+        assert!(oracle.reset_token(16) != token);
+    }
+
+    #[test]
+    fn test_ctr_round_trip() {
+        let oracle = Oracle::new(Some(b"foo".to_vec()), Some(b"baz".to_vec()));
+        let cipher_bytes = oracle.encrypt_ctr(b"bar").unwrap();
+
+        assert_eq!(oracle.decrypt_ctr(&cipher_bytes).unwrap(), b"foobarbaz");
+    }
+
+    #[test]
+    fn test_edit() {
+        let oracle = Oracle::new(None, None);
+        let plain_bytes = [b'a'; 50];
+        let cipher_bytes = oracle.encrypt_ctr(&plain_bytes).unwrap();
+
+        // Crosses a block boundary
+        let edited = oracle.edit(&cipher_bytes, 13, b"YELLOW").unwrap();
+        assert_eq!(edited.len(), 50);
+        assert_eq!(&edited[..13], &cipher_bytes[..13]);
+        assert_eq!(&edited[19..], &cipher_bytes[19..]);
+
+        let mut expected = plain_bytes.to_vec();
+        expected[13..19].copy_from_slice(b"YELLOW");
+        assert_eq!(oracle.decrypt_ctr(&edited).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_edit_past_end() {
+        let oracle = Oracle::new(None, None);
+        let cipher_bytes = oracle.encrypt_ctr(b"foo").unwrap();
+
+        let edited = oracle.edit(&cipher_bytes, 3, b"bar").unwrap();
+        assert_eq!(oracle.decrypt_ctr(&edited).unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn test_edit_invalid_offset() {
+        let oracle = Oracle::new(None, None);
+        let cipher_bytes = oracle.encrypt_ctr(b"foo").unwrap();
+
+        match oracle.edit(&cipher_bytes, 4, b"bar") {
+            Err(CryptoError::InvalidOffset(4)) => {},
+            _ => panic!("Expected InvalidOffset")
+        }
+    }
+
+    #[test]
+    fn test_cookie_oracle_quotes() {
+        for &mode in &[CipherMode::Cbc, CipherMode::Ctr] {
+            let cookie_oracle = CookieOracle::new(mode);
+            let cipher_bytes = cookie_oracle.encrypt_cookie(";admin=true;")
+                .unwrap();
+
+            assert!(!cookie_oracle.is_admin(&cipher_bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_key_as_iv() {
+        let oracle = Oracle::key_as_iv(None, None);
+        assert_eq!(oracle.iv, oracle.key);
+    }
+
+    #[test]
+    fn test_decrypt_cbc_ascii() {
+        let oracle = Oracle::new(None, None);
+        let cipher_bytes = oracle.encrypt_cbc(b"plain ascii").unwrap();
+        assert_eq!(oracle.decrypt_cbc_ascii(&cipher_bytes).unwrap(),
+            b"plain ascii");
+
+        let cipher_bytes = oracle.encrypt_cbc(b"caf\xc3\xa9").unwrap();
+        match oracle.decrypt_cbc_ascii(&cipher_bytes) {
+            Err(CryptoError::NonAscii(plain_bytes)) =>
+                assert_eq!(plain_bytes, b"caf\xc3\xa9"),
+            _ => panic!("Expected NonAscii")
+        }
+    }
+
+    #[test]
+    fn test_mac_oracle_verify() {
+        let mac_oracle = MacOracle::sha1();
+        let mac = mac_oracle.mac(b"message");
+
+        assert!(mac_oracle.verify(b"message", &mac));
+        assert!(!mac_oracle.verify(b"massage", &mac));
+
+        let mut tampered = mac.clone();
+        tampered[0] ^= 1;
+        assert!(!mac_oracle.verify(b"message", &tampered));
+    }
+
+    #[test]
+    fn test_mac_oracle_keys_differ() {
+        assert!(MacOracle::sha1().mac(b"message") !=
+            MacOracle::sha1().mac(b"message"));
+    }
+
+    #[test]
+    fn test_hmac_oracle_verify() {
+        let hmac_oracle = HmacOracle::new(Duration::from_millis(0));
+        let signature = hmac_oracle.hmac(b"foo");
+
+        assert!(hmac_oracle.verify(b"foo", &signature));
+        assert!(!hmac_oracle.verify(b"bar", &signature));
+        assert!(!hmac_oracle.verify(b"foo", &signature[..19]));
+    }
+
+    #[test]
+    fn test_insecure_compare_exits_early() {
+        let pauses = |left: &[u8], right: &[u8]| {
+            let mut count = 0;
+            let equal = insecure_compare_with(left, right, || count += 1);
+            (equal, count)
+        };
+
+        assert_eq!(pauses(b"abcd", b"xbcd"), (false, 0));
+        assert_eq!(pauses(b"abcd", b"abxd"), (false, 2));
+        assert_eq!(pauses(b"abcd", b"abcd"), (true, 4));
+        assert_eq!(pauses(b"abcd", b"abc"), (false, 3));
     }
 }
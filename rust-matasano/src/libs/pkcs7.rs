@@ -62,8 +62,17 @@ pub fn unpad(bytes: &[u8]) -> Result<Vec<u8>, PaddingError> {
 
 fn validate_padding(bytes: &[u8]) -> Result<(), PaddingError> {
     let len = bytes.len() as usize;
+
+    if len == 0 {
+        return Err(PaddingError::IncorrectPadding);
+    }
+
     let pad_len = bytes[len - 1];
 
+    if pad_len == 0 || pad_len as usize > len {
+        return Err(PaddingError::IncorrectPadding);
+    }
+
     for i in 1..pad_len {
         if bytes[len - (i as usize) - 1] != pad_len {
             return Err(PaddingError::IncorrectPadding);
@@ -165,6 +174,21 @@ mod tests {
         assert!(input.pkcs7_unpad().is_err());
     }
 
+    #[test]
+    fn test_unpad_error_zero() {
+        assert!(unpad(&[0, 5, 7, 0]).is_err());
+    }
+
+    #[test]
+    fn test_unpad_error_longer_than_input() {
+        assert!(unpad(&[5, 5, 5]).is_err());
+    }
+
+    #[test]
+    fn test_unpad_error_empty() {
+        assert!(unpad(&[]).is_err());
+    }
+
 
 }
 
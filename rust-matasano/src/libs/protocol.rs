@@ -0,0 +1,329 @@
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+
+use libs::bignum::BigUint;
+use libs::cryptor::{Aes128CbcDecryptor, Aes128CbcEncryptor, CryptoError,
+    Decryptor, Encryptor};
+use libs::dh;
+use libs::dh::Group;
+use libs::random::Random;
+use libs::sha1::Sha1;
+
+// What goes over the wire between Alice and Bob (Challenge 34, 35)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Group(Group),
+    Ack,
+    PublicKey(BigUint),
+    // AES-CBC under the shared key, with the IV at the end
+    Cipher(Vec<u8>),
+}
+
+// The CryptoError inside is only ever printed
+#[derive(Debug)]
+#[allow(dead_code)]
+pub enum ProtocolError {
+    Disconnected,
+    UnexpectedMessage,
+    // SRP's u came out as 0
+    ZeroScrambler,
+    // SRP's B was 0 mod N
+    ZeroPublicKey,
+    CryptoError(CryptoError),
+}
+
+impl From<CryptoError> for ProtocolError {
+    fn from(error: CryptoError) -> Self {
+        ProtocolError::CryptoError(error)
+    }
+}
+
+// One end of a two way channel between threads
+pub struct Endpoint<T> {
+    sender: Sender<T>,
+    receiver: Receiver<T>,
+}
+
+impl<T> Endpoint<T> {
+    pub fn send(&self, message: T) -> Result<(), ProtocolError> {
+        self.sender.send(message).map_err(|_| ProtocolError::Disconnected)
+    }
+
+    pub fn receive(&self) -> Result<T, ProtocolError> {
+        self.receiver.recv().map_err(|_| ProtocolError::Disconnected)
+    }
+}
+
+pub fn connect<T>() -> (Endpoint<T>, Endpoint<T>) {
+    let (left_sender, right_receiver) = mpsc::channel();
+    let (right_sender, left_receiver) = mpsc::channel();
+
+    (Endpoint { sender: left_sender, receiver: left_receiver },
+        Endpoint { sender: right_sender, receiver: right_receiver })
+}
+
+pub fn encrypt_message(secret: &BigUint, plain: &[u8], random: &mut Random)
+    -> Result<Vec<u8>, ProtocolError> {
+
+    let iv = &mut [0; 16];
+    random.fill_bytes(iv);
+
+    let mut cipher = Aes128CbcEncryptor(iv)
+        .encrypt(plain, &dh::aes_key::<Sha1>(secret))?;
+    cipher.extend_from_slice(iv);
+    Ok(cipher)
+}
+
+pub fn decrypt_message(secret: &BigUint, message: &[u8])
+    -> Result<Vec<u8>, ProtocolError> {
+
+    if message.len() < 16 {
+        Err(ProtocolError::UnexpectedMessage)?;
+    }
+
+    let (cipher, iv) = message.split_at(message.len() - 16);
+    Ok(Aes128CbcDecryptor(iv).decrypt(cipher, &dh::aes_key::<Sha1>(secret))?)
+}
+
+// Alice proposes the group, then sends each message and collects Bob's
+// echoes
+pub fn alice(endpoint: Endpoint<Message>, group: Group, messages: &[Vec<u8>])
+    -> Result<Vec<Vec<u8>>, ProtocolError> {
+
+    let mut random = Random::new();
+
+    endpoint.send(Message::Group(group.clone()))?;
+    match endpoint.receive()? {
+        Message::Ack => {},
+        _ => Err(ProtocolError::UnexpectedMessage)?
+    }
+
+    let keypair = group.keypair(&mut random);
+    endpoint.send(Message::PublicKey(keypair.public.clone()))?;
+    let public = to_public_key(endpoint.receive()?)?;
+    let secret = group.shared_secret(&keypair.private, &public);
+
+    let mut echoes = Vec::new();
+    for message in messages {
+        endpoint.send(Message::Cipher(
+            encrypt_message(&secret, message, &mut random)?))?;
+
+        let echo = to_cipher(endpoint.receive()?)?;
+        echoes.push(decrypt_message(&secret, &echo)?);
+    }
+
+    Ok(echoes)
+}
+
+// Bob goes along with whatever group he is offered and echoes every
+// message until Alice hangs up
+pub fn bob(endpoint: Endpoint<Message>)
+    -> Result<Vec<Vec<u8>>, ProtocolError> {
+
+    let mut random = Random::new();
+
+    let group = match endpoint.receive()? {
+        Message::Group(group) => group,
+        _ => Err(ProtocolError::UnexpectedMessage)?
+    };
+    endpoint.send(Message::Ack)?;
+
+    let public = to_public_key(endpoint.receive()?)?;
+    let keypair = group.keypair(&mut random);
+    endpoint.send(Message::PublicKey(keypair.public.clone()))?;
+    let secret = group.shared_secret(&keypair.private, &public);
+
+    let mut received = Vec::new();
+    loop {
+        let cipher = match endpoint.receive() {
+            Ok(message) => to_cipher(message)?,
+            Err(ProtocolError::Disconnected) => return Ok(received),
+            Err(error) => return Err(error)
+        };
+
+        let plain = decrypt_message(&secret, &cipher)?;
+        endpoint.send(Message::Cipher(
+            encrypt_message(&secret, &plain, &mut random)?))?;
+        received.push(plain);
+    }
+}
+
+fn to_public_key(message: Message) -> Result<BigUint, ProtocolError> {
+    match message {
+        Message::PublicKey(public) => Ok(public),
+        _ => Err(ProtocolError::UnexpectedMessage)
+    }
+}
+
+fn to_cipher(message: Message) -> Result<Vec<u8>, ProtocolError> {
+    match message {
+        Message::Cipher(cipher) => Ok(cipher),
+        _ => Err(ProtocolError::UnexpectedMessage)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    ToBob,
+    ToAlice,
+}
+
+// What Mallory does to each message she passes on. She can hand back the
+// same message, a rewritten one or one of her own.
+pub trait Strategy {
+    fn intercept(&mut self, direction: Direction, message: Message)
+        -> Result<Message, ProtocolError>;
+}
+
+pub struct Relay;
+
+impl Strategy for Relay {
+    fn intercept(&mut self, _: Direction, message: Message)
+        -> Result<Message, ProtocolError> {
+
+        Ok(message)
+    }
+}
+
+// Mallory in the middle. The protocol takes turns, so she waits on Alice,
+// then on Bob, until Alice hangs up.
+pub fn mallory<S: Strategy>(alice: Endpoint<Message>, bob: Endpoint<Message>,
+    strategy: &mut S) -> Result<(), ProtocolError> {
+
+    loop {
+        let message = match alice.receive() {
+            Ok(message) => message,
+            Err(ProtocolError::Disconnected) => return Ok(()),
+            Err(error) => return Err(error)
+        };
+        bob.send(strategy.intercept(Direction::ToBob, message)?)?;
+
+        let reply = bob.receive()?;
+        alice.send(strategy.intercept(Direction::ToAlice, reply)?)?;
+    }
+}
+
+// Plain text of both sides after a session
+pub struct Transcript {
+    pub echoes: Vec<Vec<u8>>,
+    pub received: Vec<Vec<u8>>,
+}
+
+// Alice and Bob on threads of their own, Mallory in between on this one
+pub fn run_session<S: Strategy>(group: Group, messages: &[Vec<u8>],
+    strategy: &mut S) -> Result<Transcript, ProtocolError> {
+
+    let (alice_end, mallory_alice_end) = connect();
+    let (mallory_bob_end, bob_end) = connect();
+
+    let messages = messages.to_vec();
+    let alice_thread = thread::spawn(move ||
+        alice(alice_end, group, &messages));
+    let bob_thread = thread::spawn(move || bob(bob_end));
+
+    let relayed = mallory(mallory_alice_end, mallory_bob_end, strategy);
+
+    let echoes = alice_thread.join().expect("Alice panicked")?;
+    let received = bob_thread.join().expect("Bob panicked")?;
+    relayed?;
+
+    Ok(Transcript { echoes, received })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<Vec<u8>> {
+        vec![b"YELLOW SUBMARINE".to_vec(), b"Hello, Bob".to_vec(),
+            Vec::new()]
+    }
+
+    #[test]
+    fn test_encrypt_message() {
+        let mut random = Random::new();
+        let secret = BigUint::from(1234u32);
+
+        let cipher = encrypt_message(&secret, b"Hello", &mut random).unwrap();
+        assert_eq!(cipher.len(), 32);
+        assert_eq!(decrypt_message(&secret, &cipher).unwrap(), b"Hello");
+
+        // Fresh IV each time
+        assert!(cipher !=
+            encrypt_message(&secret, b"Hello", &mut random).unwrap());
+    }
+
+    #[test]
+    fn test_decrypt_message_too_short() {
+        match decrypt_message(&BigUint::from(1u32), &[0; 15]) {
+            Err(ProtocolError::UnexpectedMessage) => {},
+            _ => panic!("Expected UnexpectedMessage")
+        }
+    }
+
+    #[test]
+    fn test_connect() {
+        let (left, right) = connect();
+        left.send(1).unwrap();
+        right.send(2).unwrap();
+
+        assert_eq!(right.receive().unwrap(), 1);
+        assert_eq!(left.receive().unwrap(), 2);
+
+        drop(left);
+        match right.receive() {
+            Err(ProtocolError::Disconnected) => {},
+            _ => panic!("Expected Disconnected")
+        }
+    }
+
+    #[test]
+    fn test_alice_and_bob() {
+        let (alice_end, bob_end) = connect();
+        let bob_thread = thread::spawn(move || bob(bob_end));
+
+        let echoes = alice(alice_end, Group::modp_1536(), &messages())
+            .unwrap();
+
+        assert_eq!(echoes, messages());
+        assert_eq!(bob_thread.join().unwrap().unwrap(), messages());
+    }
+
+    #[test]
+    fn test_relay() {
+        let transcript = run_session(Group::modp_1536(), &messages(),
+            &mut Relay).unwrap();
+
+        assert_eq!(transcript.echoes, messages());
+        assert_eq!(transcript.received, messages());
+    }
+
+    struct Tamper;
+
+    impl Strategy for Tamper {
+        fn intercept(&mut self, direction: Direction, message: Message)
+            -> Result<Message, ProtocolError> {
+
+            match (direction, message) {
+                (Direction::ToBob, Message::Cipher(mut cipher)) => {
+                    cipher[0] ^= 1;
+                    Ok(Message::Cipher(cipher))
+                },
+                (_, message) => Ok(message)
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampered_message_garbles() {
+        let transcript = run_session(Group::modp_1536(),
+            &[vec![b'a'; 40]], &mut Tamper).unwrap();
+
+        // Flipping a bit of the first block garbles it, and flips the same
+        // bit of the second
+        assert!(transcript.received[0][..16] != [b'a'; 16]);
+        assert_eq!(transcript.received[0][16], b'a' ^ 1);
+        assert_eq!(transcript.echoes, transcript.received);
+    }
+}
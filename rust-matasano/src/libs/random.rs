@@ -2,6 +2,9 @@ use rand::Rng;
 use rand::os::OsRng;
 
 use libs::utils;
+use libs::mt19937::{Mt19937, Mt19937_64};
+use libs::bignum;
+use libs::bignum::BigUint;
 
 pub struct Random {
     rng: Box<dyn Rng>
@@ -13,6 +16,15 @@ impl Random {
         Random { rng: Box::new(rng) }
     }
 
+    // Seeded generators, so that a run can be replayed
+    pub fn mt19937(seed: u32) -> Random {
+        Random { rng: Box::new(Mt19937::new(seed)) }
+    }
+
+    pub fn mt19937_64(seed: u64) -> Random {
+        Random { rng: Box::new(Mt19937_64::new(seed)) }
+    }
+
     pub fn rand(&mut self) -> i32 {
         let rand_u32 = self.rng.next_u32();
         (rand_u32 & 0x7fffffff) as i32
@@ -38,6 +50,29 @@ impl Random {
         *from + rand
     }
 
+    // Same rejection sampling as rand_range, over as many bits as the
+    // range needs
+    pub fn rand_bignum_range(&mut self, from: &BigUint, to: &BigUint)
+        -> BigUint {
+
+        assert!(to > from);
+
+        let range = to - from;
+        let bits = range.bits() as usize;
+        let mut bytes = vec![0; (bits + 7) / 8];
+        let spare = bytes.len() * 8 - bits;
+
+        loop {
+            self.fill_bytes(&mut bytes);
+            bytes[0] &= 0xff >> spare;
+
+            let num = bignum::from_bytes(&bytes);
+            if num < range {
+                return from + num;
+            }
+        }
+    }
+
     pub fn fill_bytes(&mut self, dest: &mut [u8]) {
         self.rng.fill_bytes(dest);
     }
@@ -73,5 +108,56 @@ mod tests {
             assert!(rand < 20);
         }
     }
+
+    #[test]
+    fn test_random_rand_bignum_range() {
+        let mut random = Random::new();
+        let from = bignum::from_hex("ffffffffffffffffff").unwrap();
+        let to = &from + BigUint::from(1000u32);
+
+        for _ in 0..100 {
+            let rand = random.rand_bignum_range(&from, &to);
+            assert!(rand >= from);
+            assert!(rand < to);
+        }
+    }
+
+    #[test]
+    fn test_random_rand_bignum_range_covers() {
+        let mut random = Random::mt19937(1234);
+        let mut seen = [false; 5];
+
+        for _ in 0..200 {
+            let rand = random.rand_bignum_range(&BigUint::from(3u32),
+                &BigUint::from(8u32));
+            seen[bignum::to_bytes(&rand)[0] as usize - 3] = true;
+        }
+
+        assert!(seen.iter().all(|&hit| hit));
+    }
+
+    #[test]
+    fn test_random_mt19937_replays() {
+        let mut random1 = Random::mt19937(1234);
+        let mut random2 = Random::mt19937(1234);
+
+        assert_eq!(random1.rand_bytes(&100), random2.rand_bytes(&100));
+    }
+
+    #[test]
+    fn test_random_mt19937_rand() {
+        let mut random = Random::mt19937(5489);
+        assert_eq!(random.rand(), (3499211612u32 & 0x7fffffff) as i32);
+    }
+
+    #[test]
+    fn test_random_mt19937_64_replays() {
+        let mut random1 = Random::mt19937_64(1234);
+        let mut random2 = Random::mt19937_64(1234);
+
+        for _ in 0..100 {
+            assert_eq!(random1.rand(), random2.rand());
+        }
+    }
 }
 
@@ -0,0 +1,305 @@
+// Hash API for general use, of which the attacks only need a part
+#![allow(dead_code)]
+
+const INITIAL_STATE: [u32; 5] =
+    [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+pub const BLOCK_SIZE: usize = 64;
+pub const DIGEST_SIZE: usize = 20;
+
+// SHA-1 as in FIPS 180-4. State and length are out in the open, so that
+// hashing can resume from a digest (length extension).
+#[derive(Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    // Bytes hashed so far, including any before the state was taken over
+    len: u64,
+    buffer: Vec<u8>,
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Sha1::from_state(INITIAL_STATE, 0)
+    }
+
+    // Picks up hashing after len bytes, which have to be a whole number
+    // of blocks, padding included
+    pub fn from_state(state: [u32; 5], len: u64) -> Self {
+        Sha1 { state, len, buffer: Vec::with_capacity(BLOCK_SIZE) }
+    }
+
+    // Takes over from a finished hash of len bytes, padding included
+    pub fn from_digest(digest: &[u8; DIGEST_SIZE], len: u64) -> Self {
+        let mut state = [0; 5];
+        for (index, word) in digest.chunks(4).enumerate() {
+            state[index] = ((word[0] as u32) << 24) |
+                ((word[1] as u32) << 16) | ((word[2] as u32) << 8) |
+                word[3] as u32;
+        }
+
+        Sha1::from_state(state, len)
+    }
+
+    pub fn state(&self) -> [u32; 5] {
+        self.state
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+
+        let state = &mut self.state;
+        md_update(&mut self.buffer, bytes, |block| compress(state, block));
+    }
+
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        let padding = md_padding(self.len);
+        let len = self.len;
+        self.update(&padding);
+        self.len = len;
+
+        let mut digest = [0; DIGEST_SIZE];
+        for (index, word) in self.state.iter().enumerate() {
+            digest[index * 4..index * 4 + 4]
+                .copy_from_slice(&word.to_be_bytes());
+        }
+
+        digest
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Sha1::new()
+    }
+}
+
+pub fn sha1(bytes: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut sha1 = Sha1::new();
+    sha1.update(bytes);
+    sha1.finalize()
+}
+
+// Keyed by hashing the key in front of the message. Anyone can hash more
+// on the end, given the MAC (Challenge 28, 29).
+pub fn secret_prefix_mac(key: &[u8], message: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut sha1 = Sha1::new();
+    sha1.update(key);
+    sha1.update(message);
+    sha1.finalize()
+}
+
+// Merkle-Damgard padding for a message of len bytes: a 1 bit, zeroes up
+// to 56 bytes into a block, then the length in bits as 64 bit big endian
+pub fn md_padding(len: u64) -> Vec<u8> {
+    let zeroes = (BLOCK_SIZE * 2 - 9 - len as usize % BLOCK_SIZE) %
+        BLOCK_SIZE;
+
+    let mut padding = vec![0x80];
+    padding.extend(vec![0; zeroes]);
+    padding.extend_from_slice(&(len.wrapping_mul(8)).to_be_bytes());
+    padding
+}
+
+pub fn md_pad(bytes: &[u8]) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.extend(md_padding(bytes.len() as u64));
+    padded
+}
+
+// Block buffering shared by the hashes on 64 byte blocks. Adds bytes to
+// what was left over last time and hands each whole block to compress,
+// keeping the rest for the next update.
+pub fn md_update<F>(buffer: &mut Vec<u8>, bytes: &[u8], mut compress: F)
+    where F: FnMut(&[u8]) {
+
+    buffer.extend_from_slice(bytes);
+    let full = buffer.len() - buffer.len() % BLOCK_SIZE;
+
+    for block in buffer[..full].chunks(BLOCK_SIZE) {
+        compress(block);
+    }
+
+    buffer.drain(..full);
+}
+
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0u32; 80];
+
+    for (index, word) in block.chunks(4).enumerate() {
+        w[index] = ((word[0] as u32) << 24) | ((word[1] as u32) << 16) |
+            ((word[2] as u32) << 8) | word[3] as u32;
+    }
+
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+
+    for (t, &word) in w.iter().enumerate() {
+        let (f, k) = match t {
+            0..=19 => ((b & c) | (!b & d), 0x5a827999),
+            20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6)
+        };
+
+        let temp = a.rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(word);
+
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+    state[4] = state[4].wrapping_add(e);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::hex::ToHex;
+    use crypto::digest::Digest;
+    use crypto;
+    use libs::random::Random;
+
+    #[test]
+    fn test_sha1_fips_180_abc() {
+        assert_eq!(sha1(b"abc").to_hex(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
+    #[test]
+    fn test_sha1_fips_180_two_blocks() {
+        let input = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        assert_eq!(sha1(input.as_bytes()).to_hex(),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+    }
+
+    #[test]
+    fn test_sha1_fips_180_million_a() {
+        let mut sha1 = Sha1::new();
+        for _ in 0..1000 {
+            sha1.update(&[b'a'; 1000]);
+        }
+
+        assert_eq!(sha1.finalize().to_hex(),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+
+    #[test]
+    fn test_sha1_empty() {
+        assert_eq!(sha1(b"").to_hex(),
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    }
+
+    #[test]
+    fn test_sha1_against_rust_crypto() {
+        let mut random = Random::new();
+
+        // Every length around the padding boundaries of two blocks
+        for len in 0..200 {
+            let bytes = &mut vec![0; len];
+            random.fill_bytes(bytes);
+
+            let mut expected = crypto::sha1::Sha1::new();
+            expected.input(bytes);
+
+            assert_eq!(sha1(bytes).to_hex(), expected.result_str());
+        }
+    }
+
+    #[test]
+    fn test_sha1_streaming() {
+        let bytes = (0..300).map(|byte| byte as u8).collect::<Vec<u8>>();
+
+        let mut sha1 = Sha1::new();
+        for chunk in bytes.chunks(7) {
+            sha1.update(chunk);
+        }
+
+        assert_eq!(sha1.finalize(), super::sha1(&bytes));
+    }
+
+    #[test]
+    fn test_sha1_from_state() {
+        let bytes = [b'x'; 150];
+
+        let mut first = Sha1::new();
+        first.update(&bytes[..128]);
+
+        let mut resumed = Sha1::from_state(first.state(), first.len());
+        resumed.update(&bytes[128..]);
+
+        assert_eq!(resumed.finalize(), sha1(&bytes));
+    }
+
+    #[test]
+    fn test_sha1_from_digest() {
+        let bytes = md_pad(b"YELLOW SUBMARINE");
+
+        let mut resumed = Sha1::from_digest(&sha1(b"YELLOW SUBMARINE"),
+            bytes.len() as u64);
+        resumed.update(b"suffix");
+
+        let mut expected = bytes.clone();
+        expected.extend(b"suffix");
+        assert_eq!(resumed.finalize(), sha1(&expected));
+    }
+
+    #[test]
+    fn test_secret_prefix_mac() {
+        let mac = secret_prefix_mac(b"key", b"message");
+
+        assert_eq!(mac, sha1(b"keymessage"));
+        assert!(mac != secret_prefix_mac(b"key", b"massage"));
+    }
+
+    #[test]
+    fn test_md_padding() {
+        let padding = md_padding(3);
+        assert_eq!(padding.len(), 61);
+        assert_eq!(padding[0], 0x80);
+        assert_eq!(&padding[53..], &[0, 0, 0, 0, 0, 0, 0, 24]);
+
+        // No room for the length after 56 bytes, takes another block
+        assert_eq!(md_padding(56).len(), 72);
+        assert_eq!(md_padding(55).len(), 9);
+        assert_eq!(md_padding(64).len(), 64);
+    }
+
+    #[test]
+    fn test_md_pad() {
+        for len in 0..130 {
+            assert_eq!(md_pad(&vec![0; len]).len() % BLOCK_SIZE, 0);
+        }
+    }
+
+    #[test]
+    fn test_md_update() {
+        let mut buffer = Vec::new();
+        let mut blocks = Vec::new();
+
+        md_update(&mut buffer, &[1; 40], |block| blocks.push(block.to_vec()));
+        assert!(blocks.is_empty());
+        assert_eq!(buffer.len(), 40);
+
+        md_update(&mut buffer, &[2; 100], |block| blocks.push(block.to_vec()));
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(&blocks[0][39..41], &[1, 2]);
+        assert_eq!(buffer, vec![2; 12]);
+    }
+}
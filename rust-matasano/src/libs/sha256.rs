@@ -0,0 +1,168 @@
+use libs::sha1;
+
+const INITIAL_STATE: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372,
+    0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1,
+    0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+    0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+    0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+    0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+    0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+    0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+    0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub const BLOCK_SIZE: usize = 64;
+pub const DIGEST_SIZE: usize = 32;
+
+// SHA-256 as in FIPS 180-4. Same block size and padding as SHA-1.
+#[derive(Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    len: u64,
+    buffer: Vec<u8>,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 { state: INITIAL_STATE, len: 0,
+            buffer: Vec::with_capacity(BLOCK_SIZE) }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+
+        let state = &mut self.state;
+        sha1::md_update(&mut self.buffer, bytes, |block| compress(state, block));
+    }
+
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        let padding = sha1::md_padding(self.len);
+        self.update(&padding);
+
+        let mut digest = [0; DIGEST_SIZE];
+        for (index, word) in self.state.iter().enumerate() {
+            digest[index * 4..index * 4 + 4]
+                .copy_from_slice(&word.to_be_bytes());
+        }
+
+        digest
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+pub fn sha256(bytes: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut sha256 = Sha256::new();
+    sha256.update(bytes);
+    sha256.finalize()
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+
+    for (index, word) in block.chunks(4).enumerate() {
+        w[index] = ((word[0] as u32) << 24) | ((word[1] as u32) << 16) |
+            ((word[2] as u32) << 8) | word[3] as u32;
+    }
+
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^
+            (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^
+            (w[t - 2] >> 10);
+
+        w[t] = w[t - 16].wrapping_add(s0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (&word, &k) in w.iter().zip(K.iter()) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h.wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(k)
+            .wrapping_add(word);
+
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+        *word = word.wrapping_add(*value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::hex::ToHex;
+    use crypto::digest::Digest;
+    use crypto;
+    use libs::random::Random;
+
+    #[test]
+    fn test_sha256_fips_180() {
+        assert_eq!(sha256(b"abc").to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+        let input = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        assert_eq!(sha256(input.as_bytes()).to_hex(),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+    }
+
+    #[test]
+    fn test_sha256_empty() {
+        assert_eq!(sha256(b"").to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    }
+
+    #[test]
+    fn test_sha256_against_rust_crypto() {
+        let mut random = Random::new();
+
+        for len in 0..200 {
+            let bytes = &mut vec![0; len];
+            random.fill_bytes(bytes);
+
+            let mut expected = crypto::sha2::Sha256::new();
+            expected.input(bytes);
+
+            assert_eq!(sha256(bytes).to_hex(), expected.result_str());
+        }
+    }
+
+    #[test]
+    fn test_sha256_streaming() {
+        let bytes = (0..300).map(|byte| byte as u8).collect::<Vec<u8>>();
+
+        let mut sha256 = Sha256::new();
+        for chunk in bytes.chunks(7) {
+            sha256.update(chunk);
+        }
+
+        assert_eq!(sha256.finalize(), super::sha256(&bytes));
+    }
+}
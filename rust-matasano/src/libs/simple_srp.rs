@@ -0,0 +1,393 @@
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use libs::bignum;
+use libs::bignum::BigUint;
+use libs::dh::{Group, KeyPair};
+use libs::protocol::{Endpoint, ProtocolError};
+use libs::random::Random;
+use libs::srp;
+
+// Simplified SRP of Challenge 38. B is plain g^b, with no k v in it, and u
+// comes from the server instead of from A and B. Client takes
+// S = B^(a + u x), server S = (A v^u)^b.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimpleSrpMessage {
+    Hello { email: String, public: BigUint },
+    Challenge { salt: Vec<u8>, public: BigUint, scrambler: BigUint },
+    Proof(Vec<u8>),
+    Verdict(bool),
+}
+
+enum ServerState {
+    AwaitingHello,
+    AwaitingProof { salt: Vec<u8>, key: Vec<u8> },
+    Done(bool),
+}
+
+pub struct SimpleSrpServer {
+    group: Group,
+    email: String,
+    salt: Vec<u8>,
+    verifier: BigUint,
+    state: ServerState,
+}
+
+impl SimpleSrpServer {
+    pub fn new(group: Group, email: &str, password: &[u8]) -> Self {
+        let salt = &mut [0; 16];
+        Random::new().fill_bytes(salt);
+
+        let verifier = srp::verifier(&group, salt, password);
+        SimpleSrpServer { group, email: email.to_string(), salt: salt.to_vec(),
+            verifier, state: ServerState::AwaitingHello }
+    }
+
+    pub fn logged_in(&self) -> bool {
+        match self.state {
+            ServerState::Done(verdict) => verdict,
+            _ => false
+        }
+    }
+
+    pub fn receive(&mut self, message: SimpleSrpMessage)
+        -> Result<SimpleSrpMessage, ProtocolError> {
+
+        let state = mem::replace(&mut self.state, ServerState::Done(false));
+
+        match (state, message) {
+            (ServerState::AwaitingHello,
+                SimpleSrpMessage::Hello { email, public }) => {
+
+                if email != self.email {
+                    return Ok(SimpleSrpMessage::Verdict(false));
+                }
+
+                let n = &self.group.p;
+                let mut random = Random::new();
+                let keypair = self.group.keypair(&mut random);
+                let scrambler = random.rand_bignum_range(
+                    &BigUint::from(0u32), &(BigUint::from(1u32) << 128));
+
+                // S = (A v^u)^b
+                let base = public *
+                    bignum::mod_pow(&self.verifier, &scrambler, n) % n;
+                let secret = bignum::mod_pow(&base, &keypair.private, n);
+
+                self.state = ServerState::AwaitingProof {
+                    salt: self.salt.clone(), key: srp::session_key(&secret) };
+                Ok(SimpleSrpMessage::Challenge { salt: self.salt.clone(),
+                    public: keypair.public, scrambler })
+            },
+            (ServerState::AwaitingProof { salt, key },
+                SimpleSrpMessage::Proof(mac)) => {
+
+                let verdict = mac == srp::proof(&key, &salt);
+                self.state = ServerState::Done(verdict);
+                Ok(SimpleSrpMessage::Verdict(verdict))
+            },
+            _ => Err(ProtocolError::UnexpectedMessage)
+        }
+    }
+}
+
+enum ClientState {
+    Start,
+    AwaitingChallenge(KeyPair),
+    AwaitingVerdict,
+    Done(bool),
+}
+
+pub struct SimpleSrpClient {
+    group: Group,
+    email: String,
+    password: Vec<u8>,
+    state: ClientState,
+}
+
+impl SimpleSrpClient {
+    pub fn new(group: Group, email: &str, password: &[u8]) -> Self {
+        SimpleSrpClient { group, email: email.to_string(),
+            password: password.to_vec(), state: ClientState::Start }
+    }
+
+    pub fn logged_in(&self) -> bool {
+        match self.state {
+            ClientState::Done(verdict) => verdict,
+            _ => false
+        }
+    }
+
+    pub fn hello(&mut self) -> SimpleSrpMessage {
+        let keypair = self.group.keypair(&mut Random::new());
+        let public = keypair.public.clone();
+
+        self.state = ClientState::AwaitingChallenge(keypair);
+        SimpleSrpMessage::Hello { email: self.email.clone(), public }
+    }
+
+    // The next message for the server, None once the server has decided
+    pub fn receive(&mut self, message: SimpleSrpMessage)
+        -> Result<Option<SimpleSrpMessage>, ProtocolError> {
+
+        let state = mem::replace(&mut self.state, ClientState::Done(false));
+
+        match (state, message) {
+            (ClientState::AwaitingChallenge(keypair),
+                SimpleSrpMessage::Challenge { salt, public, scrambler }) => {
+
+                // S = B^(a + u x)
+                let x = srp::private_key(&salt, &self.password);
+                let secret = bignum::mod_pow(&public,
+                    &(keypair.private + scrambler * x), &self.group.p);
+
+                self.state = ClientState::AwaitingVerdict;
+                Ok(Some(SimpleSrpMessage::Proof(srp::proof(
+                    &srp::session_key(&secret), &salt))))
+            },
+            (_, SimpleSrpMessage::Verdict(verdict)) => {
+                self.state = ClientState::Done(verdict);
+                Ok(None)
+            },
+            _ => Err(ProtocolError::UnexpectedMessage)
+        }
+    }
+}
+
+// Runs the client through one login, true if the server let it in
+pub fn login(client: &mut SimpleSrpClient,
+    endpoint: &Endpoint<SimpleSrpMessage>) -> Result<bool, ProtocolError> {
+
+    endpoint.send(client.hello())?;
+
+    while let Some(message) = client.receive(endpoint.receive()?)? {
+        endpoint.send(message)?;
+    }
+
+    Ok(client.logged_in())
+}
+
+// Serves one login, true if the client got in
+pub fn serve(server: &mut SimpleSrpServer,
+    endpoint: &Endpoint<SimpleSrpMessage>) -> Result<bool, ProtocolError> {
+
+    server.state = ServerState::AwaitingHello;
+
+    loop {
+        let reply = server.receive(endpoint.receive()?)?;
+        let done = matches!(reply, SimpleSrpMessage::Verdict(_));
+
+        endpoint.send(reply)?;
+        if done {
+            return Ok(server.logged_in());
+        }
+    }
+}
+
+// What a fake simplified SRP server gets out of a client
+pub struct CapturedLogin {
+    pub email: String,
+    pub public: BigUint,
+    pub salt: Vec<u8>,
+    pub mac: Vec<u8>,
+}
+
+// Challenge 38. Posing as the server with b = 1, B = g and u = 1 makes the
+// client's secret g^(a + x) = A g^x, which leaves the password as the only
+// unknown behind the MAC.
+pub fn capture_login(endpoint: &Endpoint<SimpleSrpMessage>,
+    group: &Group) -> Result<CapturedLogin, ProtocolError> {
+
+    let (email, public) = match endpoint.receive()? {
+        SimpleSrpMessage::Hello { email, public } => (email, public),
+        _ => return Err(ProtocolError::UnexpectedMessage)
+    };
+
+    let salt = Vec::new();
+    endpoint.send(SimpleSrpMessage::Challenge { salt: salt.clone(),
+        public: group.g.clone(), scrambler: BigUint::from(1u32) })?;
+
+    let mac = match endpoint.receive()? {
+        SimpleSrpMessage::Proof(mac) => mac,
+        _ => return Err(ProtocolError::UnexpectedMessage)
+    };
+
+    // Nothing to gain from letting on
+    endpoint.send(SimpleSrpMessage::Verdict(true))?;
+    Ok(CapturedLogin { email, public, salt, mac })
+}
+
+// Words tried between calls to the progress callback
+const CRACK_PROGRESS_STEP: usize = 50;
+
+// Tries every word as the password, spread over as many threads. Progress
+// goes to the callback as words tried and words in all.
+pub fn crack_password<F>(group: &Group, captured: &CapturedLogin,
+    words: &[String], threads: usize, progress: F) -> Option<String>
+    where F: Fn(usize, usize) + Sync {
+
+    let found = AtomicBool::new(false);
+    let tried = AtomicUsize::new(0);
+    let threads = threads.max(1);
+    // Rounded up, so that there are no more chunks than threads
+    let chunk_len = words.len() / threads + 1;
+
+    thread::scope(|scope| {
+        let (found, tried, progress) = (&found, &tried, &progress);
+
+        let workers = words.chunks(chunk_len).map(|chunk| {
+            scope.spawn(move || {
+                for word in chunk {
+                    if found.load(Ordering::Relaxed) {
+                        return None;
+                    }
+
+                    let matched = login_mac(group, captured,
+                        word.as_bytes()) == captured.mac;
+
+                    let count = tried.fetch_add(1, Ordering::Relaxed) + 1;
+                    if count % CRACK_PROGRESS_STEP == 0 {
+                        progress(count, words.len());
+                    }
+
+                    if matched {
+                        found.store(true, Ordering::Relaxed);
+                        return Some(word.clone());
+                    }
+                }
+
+                None
+            })
+        }).collect::<Vec<_>>();
+
+        workers.into_iter()
+            .filter_map(|worker| worker.join().unwrap())
+            .next()
+    })
+}
+
+// MAC the client would have sent with this password, S = A g^x
+fn login_mac(group: &Group, captured: &CapturedLogin, password: &[u8])
+    -> Vec<u8> {
+
+    let x = srp::private_key(&captured.salt, password);
+    let secret = &captured.public * bignum::mod_pow(&group.g, &x, &group.p) %
+        &group.p;
+
+    srp::proof(&srp::session_key(&secret), &captured.salt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libs::protocol;
+    use libs::utils;
+
+    const EMAIL: &str = "alice@example.com";
+
+    fn run_login(mut server: SimpleSrpServer, mut client: SimpleSrpClient)
+        -> (bool, bool) {
+
+        let (server_end, client_end) = protocol::connect();
+        let server_thread = thread::spawn(move ||
+            serve(&mut server, &server_end).unwrap());
+
+        let logged_in = login(&mut client, &client_end).unwrap();
+        (server_thread.join().unwrap(), logged_in)
+    }
+
+    #[test]
+    fn test_login() {
+        let server = SimpleSrpServer::new(Group::modp_1536(), EMAIL,
+            b"sunshine");
+        let client = SimpleSrpClient::new(Group::modp_1536(), EMAIL,
+            b"sunshine");
+
+        assert_eq!(run_login(server, client), (true, true));
+    }
+
+    #[test]
+    fn test_login_wrong_password() {
+        let server = SimpleSrpServer::new(Group::modp_1536(), EMAIL,
+            b"sunshine");
+        let client = SimpleSrpClient::new(Group::modp_1536(), EMAIL,
+            b"moonlight");
+
+        assert_eq!(run_login(server, client), (false, false));
+    }
+
+    #[test]
+    fn test_login_unknown_user() {
+        let server = SimpleSrpServer::new(Group::modp_1536(), EMAIL,
+            b"sunshine");
+        let client = SimpleSrpClient::new(Group::modp_1536(),
+            "bob@example.com", b"sunshine");
+
+        assert_eq!(run_login(server, client), (false, false));
+    }
+
+    fn captured(password: &[u8]) -> CapturedLogin {
+        let (server_end, client_end) = protocol::connect();
+        let password = password.to_vec();
+        let client_thread = thread::spawn(move || {
+            let mut client = SimpleSrpClient::new(Group::modp_1536(),
+                EMAIL, &password);
+            login(&mut client, &client_end).unwrap()
+        });
+
+        let captured = capture_login(&server_end, &Group::modp_1536())
+            .unwrap();
+
+        // The client can't tell
+        assert!(client_thread.join().unwrap());
+        captured
+    }
+
+    #[test]
+    fn test_crack_password() {
+        let captured = captured(b"sunshine");
+        let words = utils::read_lines("../resources/38.txt");
+
+        let password = crack_password(&Group::modp_1536(), &captured,
+            &words, 4, |tried, total| assert!(tried <= total));
+
+        assert_eq!(captured.email, EMAIL);
+        assert_eq!(password, Some("sunshine".to_string()));
+    }
+
+    #[test]
+    fn test_crack_password_progress() {
+        let captured = captured(b"not in the list");
+        let words = (0..120).map(|i| format!("word{}", i))
+            .collect::<Vec<String>>();
+        let calls = AtomicUsize::new(0);
+
+        let password = crack_password(&Group::modp_1536(), &captured,
+            &words, 3, |tried, total| {
+                assert_eq!(total, 120);
+                assert_eq!(tried % CRACK_PROGRESS_STEP, 0);
+                calls.fetch_add(1, Ordering::Relaxed);
+            });
+
+        assert_eq!(password, None);
+        assert_eq!(calls.load(Ordering::Relaxed), 120 / CRACK_PROGRESS_STEP);
+    }
+
+    #[test]
+    fn test_crack_password_single_thread() {
+        let captured = captured(b"w3");
+        let words = vec!["w1".to_string(), "w2".to_string(),
+            "w3".to_string()];
+
+        assert_eq!(crack_password(&Group::modp_1536(), &captured, &words,
+            1, |_, _| {}), Some("w3".to_string()));
+    }
+
+    #[test]
+    fn test_login_mac() {
+        let captured = captured(b"sunshine");
+        assert_eq!(login_mac(&Group::modp_1536(), &captured,
+            b"sunshine"), captured.mac);
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+use std::mem;
+
+use libs::bignum;
+use libs::bignum::BigUint;
+use libs::dh::{Group, KeyPair};
+use libs::hmac;
+use libs::protocol::{Endpoint, ProtocolError};
+use libs::random::Random;
+use libs::sha256;
+use libs::sha256::Sha256;
+
+// SRP-6a (RFC 5054), except that x is H(salt | password) and the proof is
+// an HMAC of the salt, as in Challenge 36
+#[derive(Clone, Debug, PartialEq)]
+pub enum SrpMessage {
+    // I and A
+    Hello { email: String, public: BigUint },
+    // s and B
+    Challenge { salt: Vec<u8>, public: BigUint },
+    Proof(Vec<u8>),
+    Verdict(bool),
+}
+
+enum ServerState {
+    AwaitingHello,
+    AwaitingProof { salt: Vec<u8>, key: Vec<u8> },
+    Done(bool),
+}
+
+pub struct SrpServer {
+    group: Group,
+    // Salt and verifier by email
+    users: HashMap<String, (Vec<u8>, BigUint)>,
+    check_public: bool,
+    state: ServerState,
+}
+
+impl SrpServer {
+    pub fn new(group: Group) -> Self {
+        SrpServer { group, users: HashMap::new(), check_public: true,
+            state: ServerState::AwaitingHello }
+    }
+
+    // Takes any A, zero mod N included (Challenge 37). A u of 0 still
+    // gets turned down.
+    pub fn unchecked(group: Group) -> Self {
+        SrpServer { check_public: false, ..SrpServer::new(group) }
+    }
+
+    pub fn register(&mut self, email: &str, password: &[u8]) {
+        let salt = &mut [0; 16];
+        Random::new().fill_bytes(salt);
+
+        let verifier = verifier(&self.group, salt, password);
+        self.users.insert(email.to_string(), (salt.to_vec(), verifier));
+    }
+
+    pub fn logged_in(&self) -> bool {
+        match self.state {
+            ServerState::Done(verdict) => verdict,
+            _ => false
+        }
+    }
+
+    pub fn receive(&mut self, message: SrpMessage)
+        -> Result<SrpMessage, ProtocolError> {
+
+        let state = mem::replace(&mut self.state, ServerState::Done(false));
+
+        match (state, message) {
+            (ServerState::AwaitingHello,
+                SrpMessage::Hello { email, public }) => {
+
+                let (salt, verifier) = match self.users.get(&email) {
+                    Some(user) => user.clone(),
+                    None => return Ok(SrpMessage::Verdict(false))
+                };
+
+                let n = &self.group.p;
+                if self.check_public && &public % n == BigUint::from(0u32) {
+                    return Ok(SrpMessage::Verdict(false));
+                }
+
+                // B = k v + g^b
+                let keypair = self.group.keypair(&mut Random::new());
+                let server_public = (multiplier(&self.group) * &verifier +
+                    &keypair.public) % n;
+
+                // S = (A v^u)^b
+                let u = check_scrambler(scrambler(&self.group, &public,
+                    &server_public))?;
+                let base = public * bignum::mod_pow(&verifier, &u, n) % n;
+                let secret = bignum::mod_pow(&base, &keypair.private, n);
+
+                self.state = ServerState::AwaitingProof { salt: salt.clone(),
+                    key: session_key(&secret) };
+                Ok(SrpMessage::Challenge { salt, public: server_public })
+            },
+            (ServerState::AwaitingProof { salt, key },
+                SrpMessage::Proof(mac)) => {
+
+                let verdict = mac == proof(&key, &salt);
+                self.state = ServerState::Done(verdict);
+                Ok(SrpMessage::Verdict(verdict))
+            },
+            _ => Err(ProtocolError::UnexpectedMessage)
+        }
+    }
+}
+
+enum ClientState {
+    Start,
+    AwaitingChallenge(KeyPair),
+    AwaitingVerdict,
+    Done(bool),
+}
+
+pub struct SrpClient {
+    group: Group,
+    email: String,
+    password: Vec<u8>,
+    state: ClientState,
+}
+
+impl SrpClient {
+    pub fn new(group: Group, email: &str, password: &[u8]) -> Self {
+        SrpClient { group, email: email.to_string(),
+            password: password.to_vec(), state: ClientState::Start }
+    }
+
+    pub fn logged_in(&self) -> bool {
+        match self.state {
+            ClientState::Done(verdict) => verdict,
+            _ => false
+        }
+    }
+
+    pub fn hello(&mut self) -> SrpMessage {
+        let keypair = self.group.keypair(&mut Random::new());
+        let public = keypair.public.clone();
+
+        self.state = ClientState::AwaitingChallenge(keypair);
+        SrpMessage::Hello { email: self.email.clone(), public }
+    }
+
+    // The next message for the server, None once the server has decided
+    pub fn receive(&mut self, message: SrpMessage)
+        -> Result<Option<SrpMessage>, ProtocolError> {
+
+        let state = mem::replace(&mut self.state, ClientState::Done(false));
+
+        match (state, message) {
+            (ClientState::AwaitingChallenge(keypair),
+                SrpMessage::Challenge { salt, public }) => {
+
+                // A B of 0 mod N would give the server's secret away the
+                // same as Challenge 37 does the client's
+                let n = &self.group.p;
+                if &public % n == BigUint::from(0u32) {
+                    return Err(ProtocolError::ZeroPublicKey);
+                }
+
+                let u = check_scrambler(scrambler(&self.group,
+                    &keypair.public, &public))?;
+                let x = private_key(&salt, &self.password);
+
+                // S = (B - k g^x)^(a + u x), kept positive mod N
+                let kgx = multiplier(&self.group) *
+                    bignum::mod_pow(&self.group.g, &x, n) % n;
+                let base = (public % n + n - kgx) % n;
+                let secret = bignum::mod_pow(&base, &(keypair.private + u * x),
+                    n);
+
+                self.state = ClientState::AwaitingVerdict;
+                Ok(Some(SrpMessage::Proof(proof(&session_key(&secret),
+                    &salt))))
+            },
+            (_, SrpMessage::Verdict(verdict)) => {
+                self.state = ClientState::Done(verdict);
+                Ok(None)
+            },
+            _ => Err(ProtocolError::UnexpectedMessage)
+        }
+    }
+}
+
+// Runs the client through one login, true if the server let it in
+pub fn login(client: &mut SrpClient, endpoint: &Endpoint<SrpMessage>)
+    -> Result<bool, ProtocolError> {
+
+    endpoint.send(client.hello())?;
+
+    while let Some(message) = client.receive(endpoint.receive()?)? {
+        endpoint.send(message)?;
+    }
+
+    Ok(client.logged_in())
+}
+
+// Serves one login, true if the client got in
+pub fn serve(server: &mut SrpServer, endpoint: &Endpoint<SrpMessage>)
+    -> Result<bool, ProtocolError> {
+
+    server.state = ServerState::AwaitingHello;
+
+    loop {
+        let reply = server.receive(endpoint.receive()?)?;
+        let done = matches!(reply, SrpMessage::Verdict(_));
+
+        endpoint.send(reply)?;
+        if done {
+            return Ok(server.logged_in());
+        }
+    }
+}
+
+// Challenge 37. With A a multiple of N, the server's secret (A v^u)^b is 0
+// whatever the password, and so is the key the proof takes.
+pub fn zero_key_login(endpoint: &Endpoint<SrpMessage>, email: &str,
+    public: BigUint) -> Result<bool, ProtocolError> {
+
+    endpoint.send(SrpMessage::Hello { email: email.to_string(), public })?;
+
+    let salt = match endpoint.receive()? {
+        SrpMessage::Challenge { salt, .. } => salt,
+        SrpMessage::Verdict(verdict) => return Ok(verdict),
+        _ => Err(ProtocolError::UnexpectedMessage)?
+    };
+
+    let key = session_key(&BigUint::from(0u32));
+    endpoint.send(SrpMessage::Proof(proof(&key, &salt)))?;
+
+    match endpoint.receive()? {
+        SrpMessage::Verdict(verdict) => Ok(verdict),
+        _ => Err(ProtocolError::UnexpectedMessage)
+    }
+}
+
+// x = H(salt | password)
+pub fn private_key(salt: &[u8], password: &[u8]) -> BigUint {
+    let mut bytes = salt.to_vec();
+    bytes.extend_from_slice(password);
+    bignum::from_bytes(&sha256::sha256(&bytes))
+}
+
+// v = g^x
+pub fn verifier(group: &Group, salt: &[u8], password: &[u8]) -> BigUint {
+    bignum::mod_pow(&group.g, &private_key(salt, password), &group.p)
+}
+
+// k = H(N | PAD(g))
+pub fn multiplier(group: &Group) -> BigUint {
+    let len = bignum::to_bytes(&group.p).len();
+    hash_padded(&[&group.p, &group.g], len)
+}
+
+// u = H(PAD(A) | PAD(B))
+pub fn scrambler(group: &Group, client_public: &BigUint,
+    server_public: &BigUint) -> BigUint {
+
+    let len = bignum::to_bytes(&group.p).len();
+    hash_padded(&[client_public, server_public], len)
+}
+
+// With u = 0 the password drops out of S, so both sides give up on it
+fn check_scrambler(u: BigUint) -> Result<BigUint, ProtocolError> {
+    if u == BigUint::from(0u32) {
+        return Err(ProtocolError::ZeroScrambler);
+    }
+
+    Ok(u)
+}
+
+// K = H(S)
+pub fn session_key(secret: &BigUint) -> Vec<u8> {
+    sha256::sha256(&bignum::to_bytes(secret)).to_vec()
+}
+
+pub fn proof(key: &[u8], salt: &[u8]) -> Vec<u8> {
+    hmac::hmac::<Sha256>(key, salt)
+}
+
+// Hash of the numbers, each left padded with zeroes to len bytes
+fn hash_padded(nums: &[&BigUint], len: usize) -> BigUint {
+    let mut bytes = Vec::new();
+    for num in nums {
+        let num_bytes = bignum::to_bytes(num);
+        bytes.extend(vec![0; len.saturating_sub(num_bytes.len())]);
+        bytes.extend(num_bytes);
+    }
+
+    bignum::from_bytes(&sha256::sha256(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use libs::protocol;
+
+    const EMAIL: &str = "alice@example.com";
+
+    // Server and client on either end of a channel. Results of the server
+    // and of the client, in that order.
+    fn run_login(server: SrpServer, mut client: SrpClient)
+        -> (Result<bool, ProtocolError>, Result<bool, ProtocolError>) {
+
+        let (server_end, client_end) = protocol::connect();
+        let server_thread = thread::spawn(move || {
+            let mut server = server;
+            serve(&mut server, &server_end)
+        });
+
+        let client_result = login(&mut client, &client_end);
+        (server_thread.join().unwrap(), client_result)
+    }
+
+    fn server() -> SrpServer {
+        let mut server = SrpServer::new(Group::modp_1536());
+        server.register(EMAIL, b"correct horse");
+        server
+    }
+
+    #[test]
+    fn test_login() {
+        let client = SrpClient::new(Group::modp_1536(), EMAIL,
+            b"correct horse");
+        let (server_result, client_result) = run_login(server(), client);
+
+        assert!(server_result.unwrap());
+        assert!(client_result.unwrap());
+    }
+
+    #[test]
+    fn test_login_wrong_password() {
+        let client = SrpClient::new(Group::modp_1536(), EMAIL,
+            b"battery staple");
+        let (server_result, client_result) = run_login(server(), client);
+
+        assert!(!server_result.unwrap());
+        assert!(!client_result.unwrap());
+    }
+
+    #[test]
+    fn test_login_unknown_user() {
+        let client = SrpClient::new(Group::modp_1536(), "bob@example.com",
+            b"correct horse");
+        let (server_result, client_result) = run_login(server(), client);
+
+        assert!(!server_result.unwrap());
+        assert!(!client_result.unwrap());
+    }
+
+    #[test]
+    fn test_server_rejects_zero_public_key() {
+        let mut server = server();
+        let hello = SrpMessage::Hello { email: EMAIL.to_string(),
+            public: Group::modp_1536().p };
+
+        assert_eq!(server.receive(hello).unwrap(),
+            SrpMessage::Verdict(false));
+    }
+
+    #[test]
+    fn test_client_rejects_zero_public_key() {
+        for multiple in 0..3u32 {
+            let mut client = SrpClient::new(Group::modp_1536(), EMAIL,
+                b"correct horse");
+            client.hello();
+            let challenge = SrpMessage::Challenge { salt: vec![0; 16],
+                public: Group::modp_1536().p * multiple };
+
+            match client.receive(challenge) {
+                Err(ProtocolError::ZeroPublicKey) => {},
+                _ => panic!("Expected ZeroPublicKey")
+            }
+        }
+    }
+
+    #[test]
+    fn test_server_unexpected_message() {
+        match server().receive(SrpMessage::Proof(vec![0; 32])) {
+            Err(ProtocolError::UnexpectedMessage) => {},
+            _ => panic!("Expected UnexpectedMessage")
+        }
+    }
+
+    fn zero_key(server: SrpServer, multiple: u32)
+        -> Result<bool, ProtocolError> {
+
+        let public = Group::modp_1536().p * multiple;
+        let (server_end, client_end) = protocol::connect();
+        let server_thread = thread::spawn(move || {
+            let mut server = server;
+            serve(&mut server, &server_end)
+        });
+
+        let logged_in = zero_key_login(&client_end, EMAIL, public)?;
+        assert_eq!(server_thread.join().unwrap()?, logged_in);
+        Ok(logged_in)
+    }
+
+    #[test]
+    fn test_zero_key_login() {
+        for multiple in 0..3 {
+            let mut server = SrpServer::unchecked(Group::modp_1536());
+            server.register(EMAIL, b"correct horse");
+
+            assert!(zero_key(server, multiple).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_zero_key_login_checked() {
+        for multiple in 0..3 {
+            let mut server = SrpServer::new(Group::modp_1536());
+            server.register(EMAIL, b"correct horse");
+
+            assert!(!zero_key(server, multiple).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_check_scrambler() {
+        match check_scrambler(BigUint::from(0u32)) {
+            Err(ProtocolError::ZeroScrambler) => {},
+            _ => panic!("Expected ZeroScrambler")
+        }
+
+        assert_eq!(check_scrambler(BigUint::from(1u32)).unwrap(),
+            BigUint::from(1u32));
+    }
+
+    #[test]
+    fn test_verifier() {
+        let group = Group::new(BigUint::from(23u32), BigUint::from(5u32));
+        let x = private_key(b"salt", b"password") % BigUint::from(22u32);
+
+        // g has order 22 mod 23
+        assert_eq!(verifier(&group, b"salt", b"password"),
+            bignum::mod_pow(&group.g, &x, &group.p));
+    }
+
+    #[test]
+    fn test_hash_padded() {
+        let (one, two) = (BigUint::from(1u32), BigUint::from(2u32));
+
+        assert_eq!(hash_padded(&[&one, &two], 2),
+            bignum::from_bytes(&sha256::sha256(&[0, 1, 0, 2])));
+    }
+}
@@ -0,0 +1,552 @@
+use std::cell::Cell;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use rustc_serialize::hex::ToHex;
+
+use libs::analyzer::AnalyzerError;
+use libs::http;
+
+// Something that checks a MAC and leaks through timing how many leading
+// bytes of it are right. Each try comes back as whether the MAC was
+// accepted and how long the check took.
+pub trait TimedVerifier {
+    fn verify(&self, mac: &[u8]) -> io::Result<(bool, Duration)>;
+}
+
+// The timing-server endpoint, /test?file=...&signature=...
+pub struct HttpVerifier {
+    addr: SocketAddr,
+    file: String,
+}
+
+impl HttpVerifier {
+    pub fn new(addr: SocketAddr, file: &str) -> Self {
+        HttpVerifier { addr, file: file.to_string() }
+    }
+}
+
+impl TimedVerifier for HttpVerifier {
+    fn verify(&self, mac: &[u8]) -> io::Result<(bool, Duration)> {
+        let target = format!("/test?file={}&signature={}", self.file,
+            mac.to_hex());
+
+        let start = Instant::now();
+        let status = http::get(self.addr, &target)?;
+        Ok((status == 200, start.elapsed()))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Statistic {
+    Median,
+    // Mean after dropping this fraction of samples from each end
+    TrimmedMean(f64),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimingConfig {
+    pub statistic: Statistic,
+    // Samples per guess on the first pass over a byte
+    pub min_samples: usize,
+    // Leading guesses keep doubling their samples up to this many, until
+    // the best one stands clear of the second
+    pub max_samples: usize,
+    // How far the best guess has to be ahead of the second, in median
+    // absolute deviations of all guesses
+    pub confidence: f64,
+    // Leading guesses to keep sampling, and to fall back on
+    pub candidates: usize,
+    pub max_backtracks: usize,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        TimingConfig { statistic: Statistic::Median, min_samples: 5,
+            max_samples: 80, confidence: 4.0, candidates: 8,
+            max_backtracks: 20 }
+    }
+}
+
+// Recovers a MAC a byte at a time from how long a verifier takes to reject
+// it (Challenge 32). A right byte makes the check go on to the next one,
+// so it stands out as the slowest guess, once enough samples are in to
+// see past the noise.
+//
+// Wrong picks give themselves away on the next byte: all of its guesses
+// get rejected as early as the wrong byte, and the typical time fails to
+// go up by a leak. The attack then backs up and moves on to the runner
+// up.
+pub struct TimingAttack<'a> {
+    verifier: &'a dyn TimedVerifier,
+    mac_len: usize,
+    config: TimingConfig,
+    queries: Cell<usize>,
+    backtracks: Cell<usize>,
+}
+
+struct Level {
+    // Guesses with how far they stand above the baseline, best first and
+    // the current pick at the head
+    candidates: Vec<(u8, f64)>,
+    baseline: f64,
+}
+
+enum Measured {
+    Accepted,
+    Ranked(Level),
+}
+
+impl<'a> TimingAttack<'a> {
+    pub fn new(verifier: &'a dyn TimedVerifier, mac_len: usize,
+        config: TimingConfig) -> Self {
+
+        TimingAttack { verifier, mac_len, config, queries: Cell::new(0),
+            backtracks: Cell::new(0) }
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.get()
+    }
+
+    pub fn backtracks(&self) -> usize {
+        self.backtracks.get()
+    }
+
+    // Recovers the first count bytes. Short of the whole MAC, one more
+    // byte gets measured to confirm the last one.
+    pub fn recover(&self, count: usize)
+        -> Result<Vec<u8>, AnalyzerError> {
+
+        let count = count.min(self.mac_len);
+        let levels = if count < self.mac_len { count + 1 } else { count };
+
+        let mut mac = vec![0; self.mac_len];
+        let mut stack: Vec<Level> = Vec::new();
+
+        while stack.len() < levels {
+            let index = stack.len();
+
+            let level = match self.measure(&mut mac, index)? {
+                Measured::Accepted => return Ok(mac[..count].to_vec()),
+                Measured::Ranked(level) => level
+            };
+
+            let consistent = stack.last().map_or(true, |previous| {
+                let leak = previous.candidates[0].1;
+                level.baseline - previous.baseline > leak / 2.0
+            });
+
+            // Every guess of the last byte was turned down
+            let dead_end = index == self.mac_len - 1;
+
+            if consistent && !dead_end {
+                mac[index] = level.candidates[0].0;
+                stack.push(level);
+            } else {
+                self.backtrack(&mut mac, &mut stack)?;
+            }
+        }
+
+        Ok(mac[..count].to_vec())
+    }
+
+    // Drops the pick of the last level for its runner up, going further
+    // back for levels that have run out
+    fn backtrack(&self, mac: &mut [u8], stack: &mut Vec<Level>)
+        -> Result<(), AnalyzerError> {
+
+        self.backtracks.set(self.backtracks.get() + 1);
+        if self.backtracks.get() > self.config.max_backtracks {
+            return Err(AnalyzerError::TimingInconclusive);
+        }
+
+        while let Some(mut previous) = stack.pop() {
+            previous.candidates.remove(0);
+
+            if let Some(&(next, _)) = previous.candidates.first() {
+                mac[stack.len()] = next;
+                stack.push(previous);
+                return Ok(());
+            }
+        }
+
+        Err(AnalyzerError::TimingInconclusive)
+    }
+
+    fn measure(&self, mac: &mut [u8], index: usize)
+        -> Result<Measured, AnalyzerError> {
+
+        for byte in mac[index + 1..].iter_mut() {
+            *byte = 0;
+        }
+
+        let mut samples = vec![Vec::new(); 256];
+        for (guess, times) in samples.iter_mut().enumerate() {
+            if self.sample(mac, index, guess as u8, self.config.min_samples,
+                times)? {
+
+                return Ok(Measured::Accepted);
+            }
+        }
+
+        // A first pass this short leaves some guess among the 256 with
+        // mostly spikes, so the leaders always get another look before
+        // the verdict
+        let mut rounds = self.config.min_samples;
+        let mut stats = self.rank(&samples);
+        let mut leader = None;
+
+        loop {
+            for &(_, guess) in stats.iter().take(self.config.candidates) {
+                if self.sample(mac, index, guess, rounds,
+                    &mut samples[guess as usize])? {
+
+                    return Ok(Measured::Accepted);
+                }
+            }
+
+            rounds *= 2;
+            stats = self.rank(&samples);
+
+            let values = stats.iter().map(|stat| stat.0).collect::<Vec<f64>>();
+            let baseline = median(&values);
+            let spread = median(&values.iter()
+                .map(|value| (value - baseline).abs())
+                .collect::<Vec<f64>>());
+
+            // A lead built on a few spikes rarely survives another round
+            let gap = stats[0].0 - stats[1].0;
+            let confident = gap > 0.0 &&
+                gap >= self.config.confidence * spread &&
+                leader == Some(stats[0].1);
+            leader = Some(stats[0].1);
+
+            if confident || rounds >= self.config.max_samples {
+                let candidates = stats.iter()
+                    .take(self.config.candidates)
+                    .map(|&(stat, guess)| (guess, stat - baseline))
+                    .collect();
+
+                return Ok(Measured::Ranked(Level { candidates, baseline }));
+            }
+        }
+    }
+
+    // Guesses by their statistic, slowest first
+    fn rank(&self, samples: &[Vec<f64>]) -> Vec<(f64, u8)> {
+        let mut stats = samples.iter()
+            .enumerate()
+            .map(|(guess, times)| (self.statistic(times), guess as u8))
+            .collect::<Vec<(f64, u8)>>();
+
+        stats.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        stats
+    }
+
+    // Adds count timings of the guess at index, in nanoseconds. True if
+    // the verifier accepted it.
+    fn sample(&self, mac: &mut [u8], index: usize, guess: u8, count: usize,
+        times: &mut Vec<f64>) -> Result<bool, AnalyzerError> {
+
+        mac[index] = guess;
+
+        for _ in 0..count {
+            self.queries.set(self.queries.get() + 1);
+
+            let (accepted, time) = self.verifier.verify(mac)?;
+            if accepted {
+                return Ok(true);
+            }
+
+            times.push(time.as_nanos() as f64);
+        }
+
+        Ok(false)
+    }
+
+    fn statistic(&self, times: &[f64]) -> f64 {
+        match self.config.statistic {
+            Statistic::Median => median(times),
+            Statistic::TrimmedMean(fraction) => trimmed_mean(times, fraction)
+        }
+    }
+}
+
+// 0 for no values at all
+pub fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+// Trims at most half the values from each end, whatever the fraction, and
+// falls back on the median when that leaves none
+pub fn trimmed_mean(values: &[f64], fraction: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let trim = ((sorted.len() as f64 * fraction) as usize)
+        .min(sorted.len() / 2);
+    let kept = &sorted[trim..sorted.len() - trim];
+
+    if kept.is_empty() {
+        median(values)
+    } else {
+        kept.iter().sum::<f64>() / kept.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+    use std::cell::RefCell;
+    use libs::oracle::HmacOracle;
+    use libs::random::Random;
+    use libs::sha1;
+
+    // HmacOracle called in process, no network in the way
+    struct HmacVerifier<'a> {
+        hmac_oracle: &'a HmacOracle,
+        file: Vec<u8>,
+    }
+
+    impl<'a> HmacVerifier<'a> {
+        fn new(hmac_oracle: &'a HmacOracle, file: &[u8]) -> Self {
+            HmacVerifier { hmac_oracle, file: file.to_vec() }
+        }
+    }
+
+    impl<'a> TimedVerifier for HmacVerifier<'a> {
+        fn verify(&self, mac: &[u8]) -> io::Result<(bool, Duration)> {
+            let start = Instant::now();
+            let accepted = self.hmac_oracle.verify(&self.file, mac);
+            Ok((accepted, start.elapsed()))
+        }
+    }
+
+    // Fake verifier on a simulated clock. Each try costs a base time, a
+    // leak per matching byte, uniform jitter and now and then a large
+    // spike, all without sleeping.
+    struct SimulatedVerifier {
+        mac: Vec<u8>,
+        leak: Duration,
+        jitter: Duration,
+        random: RefCell<Random>,
+        elapsed: Cell<Duration>,
+    }
+
+    impl SimulatedVerifier {
+        fn new(mac: &[u8], leak: Duration, jitter: Duration, seed: u32)
+            -> Self {
+
+            SimulatedVerifier { mac: mac.to_vec(), leak, jitter,
+                random: RefCell::new(Random::mt19937(seed)),
+                elapsed: Cell::new(Duration::from_millis(0)) }
+        }
+
+        // Simulated time spent on all tries so far
+        fn elapsed(&self) -> Duration {
+            self.elapsed.get()
+        }
+    }
+
+    impl TimedVerifier for SimulatedVerifier {
+        fn verify(&self, mac: &[u8]) -> io::Result<(bool, Duration)> {
+            let matching = self.mac.iter()
+                .zip(mac.iter())
+                .take_while(|&(left, right)| left == right)
+                .count();
+
+            let mut random = self.random.borrow_mut();
+            let jitter_micros = self.jitter.as_micros() as i32;
+            let mut micros = 1000 + random.rand_range(&0, &(jitter_micros + 1));
+
+            if random.rand_range(&0, &16) == 0 {
+                micros += jitter_micros * 10;
+            }
+
+            let time = Duration::from_micros(micros as u64) +
+                self.leak * matching as u32;
+            self.elapsed.set(self.elapsed.get() + time);
+
+            let accepted = matching == self.mac.len() &&
+                mac.len() == self.mac.len();
+            Ok((accepted, time))
+        }
+    }
+
+    fn simulated(leak_millis: u64, jitter_millis: u64)
+        -> (Vec<u8>, SimulatedVerifier) {
+
+        let mac = sha1::sha1(b"foo").to_vec();
+        let verifier = SimulatedVerifier::new(&mac,
+            Duration::from_millis(leak_millis),
+            Duration::from_millis(jitter_millis), 32);
+
+        (mac, verifier)
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), 2.5);
+        assert_eq!(median(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        // Spikes at either end get dropped
+        let values = [100.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, -50.0];
+        assert_eq!(trimmed_mean(&values, 0.1), 4.5);
+        assert_eq!(trimmed_mean(&[1.0, 2.0], 0.5), 1.5);
+    }
+
+    #[test]
+    fn test_trimmed_mean_out_of_range() {
+        assert_eq!(trimmed_mean(&[1.0, 2.0, 9.0], 0.8), 2.0);
+        assert_eq!(trimmed_mean(&[1.0, 2.0, 9.0], 1.5), 2.0);
+        assert_eq!(trimmed_mean(&[1.0, 2.0, 9.0], -1.0), 4.0);
+        assert_eq!(trimmed_mean(&[], 0.2), 0.0);
+    }
+
+    #[test]
+    fn test_simulated_verifier() {
+        let (mac, verifier) = simulated(5, 0);
+
+        let (accepted, time) = verifier.verify(&mac).unwrap();
+        assert!(accepted);
+        assert!(time >= Duration::from_millis(101));
+
+        let mut wrong = mac.clone();
+        wrong[2] ^= 1;
+        let (accepted, time) = verifier.verify(&wrong).unwrap();
+        assert!(!accepted);
+        assert!(time >= Duration::from_millis(11));
+        assert!(verifier.elapsed() >= Duration::from_millis(112));
+    }
+
+    #[test]
+    fn test_timing_attack_simulated() {
+        // A few milliseconds of leak under as much jitter
+        let (mac, verifier) = simulated(3, 3);
+        let attack = TimingAttack::new(&verifier, 20,
+            TimingConfig::default());
+
+        assert_eq!(attack.recover(20).unwrap(), mac);
+        assert!(attack.queries() > 20 * 256);
+    }
+
+    #[test]
+    fn test_timing_attack_simulated_trimmed_mean() {
+        let (mac, verifier) = simulated(3, 3);
+        let config = TimingConfig { statistic: Statistic::TrimmedMean(0.2),
+            ..TimingConfig::default() };
+        let attack = TimingAttack::new(&verifier, 20, config);
+
+        assert_eq!(attack.recover(20).unwrap(), mac);
+    }
+
+    #[test]
+    fn test_timing_attack_simulated_prefix() {
+        let (mac, verifier) = simulated(3, 3);
+        let attack = TimingAttack::new(&verifier, 20,
+            TimingConfig::default());
+
+        assert_eq!(attack.recover(4).unwrap(), &mac[..4]);
+    }
+
+    // Makes one wrong byte look right, for as long as the rest of the MAC
+    // is still zeroes
+    struct DecoyVerifier {
+        inner: SimulatedVerifier,
+        mac: Vec<u8>,
+        index: usize,
+    }
+
+    impl TimedVerifier for DecoyVerifier {
+        fn verify(&self, mac: &[u8]) -> io::Result<(bool, Duration)> {
+            let (accepted, mut time) = self.inner.verify(mac)?;
+            let index = self.index;
+
+            if mac[..index] == self.mac[..index] &&
+                mac[index] == self.mac[index] ^ 0xff &&
+                mac[index + 1..].iter().all(|&byte| byte == 0) {
+
+                time += Duration::from_millis(6);
+            }
+
+            Ok((accepted, time))
+        }
+    }
+
+    #[test]
+    fn test_timing_attack_backtracks() {
+        let (mac, inner) = simulated(3, 1);
+        let verifier = DecoyVerifier { inner, mac: mac.clone(), index: 3 };
+        let attack = TimingAttack::new(&verifier, 20,
+            TimingConfig::default());
+
+        assert_eq!(attack.recover(20).unwrap(), mac);
+        assert!(attack.backtracks() > 0);
+    }
+
+    #[test]
+    fn test_timing_attack_no_leak() {
+        let (_, verifier) = simulated(0, 3);
+        let config = TimingConfig { max_samples: 10, max_backtracks: 3,
+            ..TimingConfig::default() };
+        let attack = TimingAttack::new(&verifier, 20, config);
+
+        match attack.recover(20) {
+            Err(AnalyzerError::TimingInconclusive) => {},
+            _ => panic!("Expected TimingInconclusive")
+        }
+    }
+
+    // On the wall clock, so slow and at the mercy of the machine's load.
+    // Run with --ignored.
+    #[test]
+    #[ignore]
+    fn test_timing_attack_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hmac_oracle = Arc::new(HmacOracle::new(Duration::from_millis(3)));
+
+        let server_oracle = hmac_oracle.clone();
+        thread::spawn(move || http::serve_hmac(&listener, &server_oracle));
+
+        let verifier = HttpVerifier::new(addr, "foo");
+        let attack = TimingAttack::new(&verifier, 20,
+            TimingConfig::default());
+
+        assert_eq!(attack.recover(1).unwrap(),
+            &hmac_oracle.hmac(b"foo")[..1]);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_timing_attack_in_process() {
+        let hmac_oracle = HmacOracle::new(Duration::from_millis(2));
+        let verifier = HmacVerifier::new(&hmac_oracle, b"foo");
+        let attack = TimingAttack::new(&verifier, 20,
+            TimingConfig::default());
+
+        assert_eq!(attack.recover(1).unwrap(),
+            &hmac_oracle.hmac(b"foo")[..1]);
+    }
+}
@@ -15,6 +15,23 @@ pub fn from_base64_file(filename: &str) -> Vec<u8> {
     text.as_str().from_base64().unwrap()
 }
 
+// Each line of the file is decoded separately
+pub fn from_base64_lines(filename: &str) -> Vec<Vec<u8>> {
+    let file = File::open(filename).unwrap();
+    let buf_file = BufReader::new(&file);
+
+    buf_file.lines()
+        .map(|line| line.unwrap().as_str().from_base64().unwrap())
+        .collect()
+}
+
+pub fn read_lines(filename: &str) -> Vec<String> {
+    let file = File::open(filename).unwrap();
+    let buf_file = BufReader::new(&file);
+
+    buf_file.lines().map(|line| line.unwrap()).collect()
+}
+
 pub fn bits_in_num(num: &i32) -> i32 {
     match *num {
         0 => 0,
@@ -36,5 +53,12 @@ mod tests {
         assert_eq!(bits_in_num(&15), 4);
         assert_eq!(bits_in_num(&32), 6);
     }
+
+    #[test]
+    fn test_utils_read_lines() {
+        let words = read_lines("../resources/38.txt");
+        assert_eq!(words[0], "password");
+        assert!(words.iter().all(|word| !word.is_empty()));
+    }
 }
 
@@ -1,7 +1,9 @@
 extern crate rustc_serialize;
+#[cfg(test)]
 extern crate crypto;
 extern crate rand;
 extern crate regex;
+extern crate num_bigint;
 
 use std::env;
 use std::collections::HashMap;
@@ -24,6 +26,28 @@ mod challenge13;
 mod challenge14;
 mod challenge15;
 mod challenge16;
+mod challenge17;
+mod challenge18;
+mod challenge19;
+mod challenge20;
+mod challenge21;
+mod challenge22;
+mod challenge23;
+mod challenge24;
+mod challenge25;
+mod challenge26;
+mod challenge27;
+mod challenge28;
+mod challenge29;
+mod challenge30;
+mod challenge31;
+mod challenge32;
+mod challenge33;
+mod challenge34;
+mod challenge35;
+mod challenge36;
+mod challenge37;
+mod challenge38;
 
 fn main() {
     let mut args = env::args();
@@ -75,6 +99,28 @@ fn get_challenges_map() -> HashMap<i32, fn()> {
     challenges_map.insert(14, challenge14::run);
     challenges_map.insert(15, challenge15::run);
     challenges_map.insert(16, challenge16::run);
+    challenges_map.insert(17, challenge17::run);
+    challenges_map.insert(18, challenge18::run);
+    challenges_map.insert(19, challenge19::run);
+    challenges_map.insert(20, challenge20::run);
+    challenges_map.insert(21, challenge21::run);
+    challenges_map.insert(22, challenge22::run);
+    challenges_map.insert(23, challenge23::run);
+    challenges_map.insert(24, challenge24::run);
+    challenges_map.insert(25, challenge25::run);
+    challenges_map.insert(26, challenge26::run);
+    challenges_map.insert(27, challenge27::run);
+    challenges_map.insert(28, challenge28::run);
+    challenges_map.insert(29, challenge29::run);
+    challenges_map.insert(30, challenge30::run);
+    challenges_map.insert(31, challenge31::run);
+    challenges_map.insert(32, challenge32::run);
+    challenges_map.insert(33, challenge33::run);
+    challenges_map.insert(34, challenge34::run);
+    challenges_map.insert(35, challenge35::run);
+    challenges_map.insert(36, challenge36::run);
+    challenges_map.insert(37, challenge37::run);
+    challenges_map.insert(38, challenge38::run);
 
     challenges_map
 }
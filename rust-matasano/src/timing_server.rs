@@ -0,0 +1,37 @@
+extern crate rustc_serialize;
+extern crate rand;
+extern crate regex;
+extern crate num_bigint;
+
+use std::env;
+use std::net::TcpListener;
+use std::time::Duration;
+
+// Only the HTTP server and the HMAC oracle are used here
+#[path = "libs/mod.rs"]
+#[allow(dead_code)]
+mod libs;
+
+use libs::http;
+use libs::oracle::HmacOracle;
+
+// Usage: timing-server [port] [delay in ms]
+//
+// Serves /test?file=foo&signature=... on localhost only, 200 for a valid
+// HMAC-SHA1 of the file name and 500 otherwise, under a random key
+fn main() {
+    let mut args = env::args().skip(1);
+    let port = args.next()
+        .map(|port| port.parse::<u16>().expect("Invalid port"))
+        .unwrap_or(9000);
+    let delay = args.next()
+        .map(|delay| delay.parse::<u64>().expect("Invalid delay"))
+        .unwrap_or(50);
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .expect("Can't bind");
+    let hmac_oracle = HmacOracle::new(Duration::from_millis(delay));
+
+    println!("Listening on {}", listener.local_addr().unwrap());
+    http::serve_hmac(&listener, &hmac_oracle);
+}